@@ -0,0 +1,344 @@
+mod folding;
+
+use crate::constraint_system::ConstraintSystem;
+use crate::gadget::{
+    alloc, alloc_challenge, alloc_point, constant_point, evaluate, is_zero, mul, PointWires,
+    PoseidonGadget,
+};
+use crate::matrix::{DenseVectors, Element};
+use crate::nifs::{ivc_challenge, VerificationKey};
+use crate::r1cs::{Instance as R1csInstance, R1csStructure};
+use crate::relaxed_r1cs::Instance as RelaxedR1csInstance;
+use crate::step_circuit::StepCircuit;
+use crate::wire::Wire;
+
+use zkstd::common::{Group, Ring, TwistedEdwardsAffine};
+
+pub(crate) use folding::{fold_commitments, CommitmentFold};
+
+/// public io length of F' which is H(vk, i+1, z0, zi+1, Ui+1)
+pub(crate) const AUGMENTED_IO: usize = 1;
+
+/// augmented step circuit F'
+/// checks ui.x = H(vk, i, z0, zi, Ui), folds (Ui, ui, T) into Ui+1 and outputs H(vk, i+1, z0, F(zi), Ui+1)
+/// commitments of Ui+1 are folded by non-native arithmetic since points are not native to the
+/// scalar field
+#[derive(Clone, Debug)]
+pub(crate) struct AugmentedCircuit<C: TwistedEdwardsAffine> {
+    /// verification key digest
+    pub(crate) digest: C::Scalar,
+    /// step counter
+    pub(crate) i: usize,
+    pub(crate) z0: Vec<C::Scalar>,
+    pub(crate) zi: Vec<C::Scalar>,
+    /// running instance Ui
+    pub(crate) relaxed_instance: RelaxedR1csInstance<C>,
+    /// last step instance ui
+    pub(crate) instance: R1csInstance<C>,
+    /// commitment for cross term T
+    pub(crate) commit_t: C,
+}
+
+/// r1cs instance wires
+pub(crate) struct InstanceWires {
+    pub(crate) commit_w: PointWires,
+    pub(crate) x: Vec<Wire>,
+}
+
+/// relaxed r1cs instance wires
+pub(crate) struct RelaxedInstanceWires {
+    pub(crate) commit_w: PointWires,
+    pub(crate) commit_e: PointWires,
+    pub(crate) u: Wire,
+    pub(crate) x: Vec<Wire>,
+}
+
+impl<C: TwistedEdwardsAffine> AugmentedCircuit<C> {
+    /// base case i = 0 with trivial instances
+    pub(crate) fn base(digest: C::Scalar, z0: Vec<C::Scalar>) -> Self {
+        Self {
+            digest,
+            i: 0,
+            zi: z0.clone(),
+            z0,
+            relaxed_instance: trivial_instance(AUGMENTED_IO),
+            instance: R1csInstance {
+                commit_w: C::ADDITIVE_IDENTITY,
+                x: DenseVectors(vec![C::Scalar::zero(); AUGMENTED_IO]),
+            },
+            commit_t: C::ADDITIVE_IDENTITY,
+        }
+    }
+
+    /// running instance Ui+1 folded outside of circuit
+    pub(crate) fn folded_instance(&self) -> RelaxedR1csInstance<C> {
+        if self.i == 0 {
+            trivial_instance(AUGMENTED_IO)
+        } else {
+            let vk = VerificationKey {
+                digest: self.digest,
+            };
+            let r = ivc_challenge(&vk, &self.relaxed_instance, &self.instance, &self.commit_t);
+            self.relaxed_instance
+                .fold(self.instance.clone(), r, self.commit_t)
+        }
+    }
+
+    /// synthesize F' with step circuit F and output zi+1
+    pub(crate) fn synthesize(
+        &self,
+        cs: &mut ConstraintSystem<C>,
+        step: &impl StepCircuit<C>,
+    ) -> Vec<C::Scalar> {
+        let (zero, one) = (C::Scalar::zero(), C::Scalar::one());
+        let digest = cs.private_wire(self.digest);
+        let i = cs.private_wire(C::Scalar::from(self.i as u64));
+        let z0 = self
+            .z0
+            .iter()
+            .map(|z| cs.private_wire(*z))
+            .collect::<Vec<_>>();
+        let zi = self
+            .zi
+            .iter()
+            .map(|z| cs.private_wire(*z))
+            .collect::<Vec<_>>();
+        let relaxed_instance = alloc_relaxed_instance(cs, &self.relaxed_instance);
+        let instance = alloc_instance(cs, &self.instance);
+        let commit_t = alloc_point(cs, &self.commit_t);
+
+        let is_base = is_zero(cs, i);
+        let not_base = vec![one.into(), Element(is_base, zero - one)];
+
+        // zi = z0 at base case
+        z0.iter().zip(zi.iter()).for_each(|(z0, zi)| {
+            cs.constrain(
+                vec![is_base.into()],
+                vec![(*zi).into(), Element(*z0, zero - one)],
+                vec![],
+            )
+        });
+
+        // ui.x = H(vk, i, z0, zi, Ui) after base case
+        let hash = hash_io(cs, digest, i, (&z0, &zi), &relaxed_instance);
+        cs.constrain(
+            not_base,
+            vec![instance.x[0].into(), Element(hash, zero - one)],
+            vec![],
+        );
+
+        // fold (Ui, ui, T) into Ui+1 after base case
+        let folded_instance = fold_instance(
+            cs,
+            (digest, is_base),
+            (&relaxed_instance, &instance),
+            &commit_t,
+        );
+
+        // zi+1 = F(zi)
+        let zi1 = step.synthesize(cs, &zi);
+
+        // output H(vk, i+1, z0, zi+1, Ui+1)
+        let i1 = alloc(cs, vec![i.into(), one.into()]);
+        let hash = hash_io(cs, digest, i1, (&z0, &zi1), &folded_instance);
+        let value = cs.value(hash);
+        let output = cs.public_wire(value);
+        cs.equal_constraint(hash, output);
+
+        zi1.iter().map(|z| cs.value(*z)).collect()
+    }
+}
+
+/// r1cs structure of F' for step circuit F
+pub(crate) fn augmented_r1cs<C: TwistedEdwardsAffine>(
+    step: &impl StepCircuit<C>,
+) -> R1csStructure<C> {
+    let mut cs = ConstraintSystem::new();
+    let z0 = vec![C::Scalar::zero(); step.arity()];
+    AugmentedCircuit::base(C::Scalar::zero(), z0).synthesize(&mut cs, step);
+    cs.structure()
+}
+
+/// trivial running instance u⊥ with io length l
+pub(crate) fn trivial_instance<C: TwistedEdwardsAffine>(l: usize) -> RelaxedR1csInstance<C> {
+    RelaxedR1csInstance {
+        commit_w: C::ADDITIVE_IDENTITY,
+        commit_e: C::ADDITIVE_IDENTITY,
+        u: C::Scalar::zero(),
+        x: DenseVectors(vec![C::Scalar::zero(); l]),
+    }
+}
+
+pub(crate) fn alloc_relaxed_instance<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    instance: &RelaxedR1csInstance<C>,
+) -> RelaxedInstanceWires {
+    RelaxedInstanceWires {
+        commit_w: alloc_point(cs, &instance.commit_w),
+        commit_e: alloc_point(cs, &instance.commit_e),
+        u: cs.private_wire(instance.u),
+        x: instance.x.iter().map(|x| cs.private_wire(x)).collect(),
+    }
+}
+
+pub(crate) fn alloc_instance<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    instance: &R1csInstance<C>,
+) -> InstanceWires {
+    InstanceWires {
+        commit_w: alloc_point(cs, &instance.commit_w),
+        x: instance.x.iter().map(|x| cs.private_wire(x)).collect(),
+    }
+}
+
+pub(crate) fn absorb_relaxed_instance<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    transcript: &mut PoseidonGadget<C>,
+    instance: &RelaxedInstanceWires,
+) {
+    transcript.absorb_point(cs, &instance.commit_w);
+    transcript.absorb_point(cs, &instance.commit_e);
+    transcript.absorb(cs, instance.u);
+    instance.x.iter().for_each(|x| transcript.absorb(cs, *x));
+}
+
+/// fold (U, u, T) into U' with r ← p(vk, U, u, T) truncated to k bits after base case
+/// U'.u = 1 + r · U.u, U'.x = u.x + r · U.x and commitments of U' are folded in circuit
+pub(crate) fn fold_instance<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    (digest, is_base): (Wire, Wire),
+    (relaxed_instance, instance): (&RelaxedInstanceWires, &InstanceWires),
+    commit_t: &PointWires,
+) -> RelaxedInstanceWires {
+    let (zero, one) = (C::Scalar::zero(), C::Scalar::one());
+    let not_base = vec![one.into(), Element(is_base, zero - one)];
+    let mut transcript = PoseidonGadget::new(b"nifs");
+    transcript.absorb(cs, digest);
+    absorb_relaxed_instance(cs, &mut transcript, relaxed_instance);
+    transcript.absorb_point(cs, &instance.commit_w);
+    instance.x.iter().for_each(|x| transcript.absorb(cs, *x));
+    transcript.absorb_point(cs, commit_t);
+    let squeezed = transcript.squeeze(cs);
+    let r = alloc_challenge(cs, squeezed);
+
+    let ru = mul(cs, r.value, relaxed_instance.u);
+    let u = alloc_selected(cs, &not_base, vec![one.into(), ru.into()]);
+    let x = instance
+        .x
+        .iter()
+        .zip(relaxed_instance.x.iter())
+        .map(|(x, relaxed_x)| {
+            let rx = mul(cs, r.value, *relaxed_x);
+            alloc_selected(cs, &not_base, vec![(*x).into(), rx.into()])
+        })
+        .collect();
+
+    // W' = u.W + r · U.W and E' = 0 + r · (T + r · U.E)
+    let identity = constant_point(cs, &C::ADDITIVE_IDENTITY);
+    let (commit_w, commit_e) = fold_commitments(
+        cs,
+        CommitmentFold {
+            is_base,
+            r: &r,
+            commit_w: (&instance.commit_w, &relaxed_instance.commit_w),
+            commit_e: (&identity, &relaxed_instance.commit_e),
+            commit_t,
+        },
+    );
+    RelaxedInstanceWires {
+        commit_w,
+        commit_e,
+        u,
+        x,
+    }
+}
+
+/// H(vk, i, z0, zi, U) corresponding to `RelaxedR1csInstance::hash`
+fn hash_io<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    digest: Wire,
+    i: Wire,
+    (z0, zi): (&[Wire], &[Wire]),
+    instance: &RelaxedInstanceWires,
+) -> Wire {
+    let mut hasher = PoseidonGadget::new(b"ivc");
+    hasher.absorb(cs, digest);
+    hasher.absorb(cs, i);
+    z0.iter()
+        .chain(zi.iter())
+        .for_each(|z| hasher.absorb(cs, *z));
+    absorb_relaxed_instance(cs, &mut hasher, instance);
+    hasher.squeeze(cs)
+}
+
+/// allocate (1 - b) · (Σ elements) for not_base = 1 - b
+pub(crate) fn alloc_selected<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    not_base: &[Element<C::Scalar>],
+    elements: Vec<Element<C::Scalar>>,
+) -> Wire {
+    let value = evaluate(cs, not_base) * evaluate(cs, &elements);
+    let wire = cs.private_wire(value);
+    cs.constrain(not_base.to_vec(), elements, vec![wire.into()]);
+    wire
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{augmented_r1cs, AugmentedCircuit};
+    use crate::constraint_system::ConstraintSystem;
+    use crate::matrix::DenseVectors;
+    use crate::r1cs::Instance as R1csInstance;
+    use crate::tests::ExampleCircuit;
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use zkstd::common::CurveGroup;
+
+    #[test]
+    fn augmented_circuit_test() {
+        let digest = Scalar::from(11);
+        let z0 = vec![Scalar::from(2)];
+        let r1cs = augmented_r1cs::<Curve>(&ExampleCircuit);
+
+        // base case
+        let circuit = AugmentedCircuit::<Curve>::base(digest, z0.clone());
+        let mut cs = ConstraintSystem::new();
+        let z1 = circuit.synthesize(&mut cs, &ExampleCircuit);
+        let structure = cs.structure();
+        let u1 = circuit.folded_instance();
+
+        assert!(cs.is_sat());
+        assert_eq!((structure.m, structure.l), (r1cs.m, r1cs.l));
+        assert_eq!(z1[0], Scalar::from(15));
+        assert_eq!(cs.assignment()[0], u1.hash(digest, 1, &z0, &z1));
+
+        // first step folds base case instance
+        let circuit = AugmentedCircuit {
+            digest,
+            i: 1,
+            z0: z0.clone(),
+            zi: z1,
+            relaxed_instance: u1,
+            instance: R1csInstance {
+                commit_w: Curve::ADDITIVE_GENERATOR,
+                x: DenseVectors(vec![cs.assignment()[0]]),
+            },
+            commit_t: Curve::ADDITIVE_GENERATOR,
+        };
+        let mut cs = ConstraintSystem::new();
+        let z2 = circuit.synthesize(&mut cs, &ExampleCircuit);
+        let u2 = circuit.folded_instance();
+
+        assert!(cs.is_sat());
+        assert_eq!(cs.structure().m, r1cs.m);
+        assert_eq!(cs.assignment()[0], u2.hash(digest, 2, &z0, &z2));
+
+        // invalid io hash of last step instance
+        let mut invalid_circuit = circuit.clone();
+        invalid_circuit.instance.x = DenseVectors(vec![Scalar::one()]);
+        let mut cs = ConstraintSystem::new();
+        invalid_circuit.synthesize(&mut cs, &ExampleCircuit);
+
+        assert!(!cs.is_sat())
+    }
+}
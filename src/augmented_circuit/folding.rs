@@ -0,0 +1,51 @@
+use crate::constraint_system::ConstraintSystem;
+use crate::gadget::{
+    add_point, constant_point, scalar_mul, select_point, ChallengeWires, PointWires,
+};
+use crate::wire::Wire;
+
+use zkstd::common::TwistedEdwardsAffine;
+
+/// commitments folded as W' = Wa + r · Wb and E' = Ea + r · (T + r · Eb)
+/// strict fold of u into U takes (Wa, Wb) = (u.W, U.W) and (Ea, Eb) = (0, U.E)
+pub(crate) struct CommitmentFold<'a> {
+    /// boolean which is one at base case where folded commitments are identity
+    pub(crate) is_base: Wire,
+    /// folding challenge of instances
+    pub(crate) r: &'a ChallengeWires,
+    pub(crate) commit_w: (&'a PointWires, &'a PointWires),
+    pub(crate) commit_e: (&'a PointWires, &'a PointWires),
+    pub(crate) commit_t: &'a PointWires,
+}
+
+/// fold commitments in circuit by twisted edwards arithmetic over limbs of foreign field since
+/// points aren't native to circuit scalar field, and output (W', E') bound to folded instances
+pub(crate) fn fold_commitments<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    fold: CommitmentFold,
+) -> (PointWires, PointWires) {
+    let CommitmentFold {
+        is_base,
+        r,
+        commit_w,
+        commit_e,
+        commit_t,
+    } = fold;
+
+    // W' = Wa + r · Wb
+    let r_w = scalar_mul(cs, &r.bits, commit_w.1);
+    let folded_w = add_point(cs, commit_w.0, &r_w);
+
+    // E' = Ea + r · (T + r · Eb)
+    let r_e = scalar_mul(cs, &r.bits, commit_e.1);
+    let t_r_e = add_point(cs, commit_t, &r_e);
+    let r_t_r_e = scalar_mul(cs, &r.bits, &t_r_e);
+    let folded_e = add_point(cs, commit_e.0, &r_t_r_e);
+
+    // identity W' and E' at base case
+    let identity = constant_point(cs, &C::ADDITIVE_IDENTITY);
+    (
+        select_point(cs, is_base, &identity, &folded_w),
+        select_point(cs, is_base, &identity, &folded_e),
+    )
+}
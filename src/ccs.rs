@@ -0,0 +1,265 @@
+mod blueprint;
+mod gate;
+mod instance;
+mod witness;
+
+pub use blueprint::CcsStructure;
+pub use gate::Gate;
+pub use instance::Instance;
+pub use witness::Witness;
+
+use crate::error::Error;
+use crate::matrix::DenseVectors;
+use crate::public_param::PedersenCommitment;
+use crate::r1cs::{Instance as CccsInstance, Witness as CccsWitness};
+use crate::relaxed_r1cs::{Instance as RelaxedInstance, Witness as RelaxedWitness};
+use crate::snark::{evaluate, pad};
+
+use zkstd::common::{Group, Ring, RngCore, TwistedEdwardsAffine};
+
+/// ccs structure with committed instance (W̄, x) and witness (W, x, 1) shared with r1cs
+#[derive(Clone, Debug)]
+pub struct Cccs<C: TwistedEdwardsAffine> {
+    pub(crate) ccs: CcsStructure<C>,
+    pub(crate) instance: CccsInstance<C>,
+    pub(crate) witness: CccsWitness<C>,
+}
+
+impl<C: TwistedEdwardsAffine> Cccs<C> {
+    /// commit witness of assignment z = (x, W) for structure
+    /// z must hold l instances and witness length of structure which fits bases of pp
+    pub fn new(
+        ccs: &CcsStructure<C>,
+        z: &[C::Scalar],
+        pp: &PedersenCommitment<C>,
+        rng: impl RngCore,
+    ) -> Result<Self, Error> {
+        let witness_len = ccs.witness_len();
+        if z.len() != ccs.l + witness_len || witness_len > pp.size() {
+            return Err(Error::InvalidLength);
+        }
+        let mut witness = CccsWitness {
+            w: DenseVectors(z[ccs.l..].to_vec()),
+            x: DenseVectors(z[..ccs.l].to_vec()),
+            one: C::Scalar::one(),
+            r_w: C::Scalar::zero(),
+        };
+        let commit_w = witness.commit(pp, rng);
+        let instance = CccsInstance {
+            commit_w,
+            x: witness.x.clone(),
+        };
+        Ok(Self {
+            ccs: ccs.clone(),
+            instance,
+            witness,
+        })
+    }
+
+    /// committed instance (W̄, x)
+    pub fn instance(&self) -> &CccsInstance<C> {
+        &self.instance
+    }
+
+    /// witness (W, x, 1)
+    pub fn witness(&self) -> &CccsWitness<C> {
+        &self.witness
+    }
+
+    /// check Σ ci · ◦_{j ∈ Si} Mj · Z = 0
+    pub fn is_sat(&self) -> bool {
+        let prods = self
+            .ccs
+            .prods(self.witness.one, &self.witness.x, &self.witness.w);
+        (0..self.ccs.m).all(|i| {
+            let v = prods.iter().map(|prod| prod[i]).collect::<Vec<_>>();
+            self.ccs.combine(&v) == C::Scalar::zero()
+        })
+    }
+
+    /// linearize at rx with u = 1 and vj = M̃j · z̃(rx)
+    pub fn linearize(&self, r_x: Vec<C::Scalar>) -> Lcccs<C> {
+        let rows = self.ccs.m.next_power_of_two();
+        let v = self
+            .ccs
+            .prods(self.witness.one, &self.witness.x, &self.witness.w)
+            .iter()
+            .map(|prod| evaluate(&pad(&prod.0, rows), &r_x))
+            .collect();
+        Lcccs {
+            ccs: self.ccs.clone(),
+            instance: Instance {
+                commit_w: self.instance.commit_w,
+                u: self.witness.one,
+                x: self.witness.x.clone(),
+                r_x,
+                v,
+            },
+            witness: Witness {
+                w: self.witness.w.clone(),
+                r_w: self.witness.r_w,
+            },
+        }
+    }
+}
+
+/// ccs structure with linearized committed instance and witness
+#[derive(Clone, Debug)]
+pub struct Lcccs<C: TwistedEdwardsAffine> {
+    pub(crate) ccs: CcsStructure<C>,
+    pub(crate) instance: Instance<C>,
+    pub(crate) witness: Witness<C>,
+}
+
+impl<C: TwistedEdwardsAffine> Lcccs<C> {
+    /// trivial running instance with zero witness of length n
+    pub fn init(ccs: &CcsStructure<C>, n: usize) -> Self {
+        Self {
+            ccs: ccs.clone(),
+            instance: Instance::init(ccs),
+            witness: Witness::init(n),
+        }
+    }
+
+    /// linearized committed instance (W̄, u, x, rx, v)
+    pub fn instance(&self) -> &Instance<C> {
+        &self.instance
+    }
+
+    /// witness (W, rW)
+    pub fn witness(&self) -> &Witness<C> {
+        &self.witness
+    }
+
+    /// check vj = M̃j · z̃(rx) for z = (W, u, x)
+    pub fn is_sat(&self) -> bool {
+        let rows = self.ccs.m.next_power_of_two();
+        let Instance { u, x, r_x, v, .. } = &self.instance;
+        r_x.len() == rows.trailing_zeros() as usize
+            && v.len() == self.ccs.t()
+            && self
+                .ccs
+                .prods(*u, x, &self.witness.w)
+                .iter()
+                .zip(v.iter())
+                .all(|(prod, v)| evaluate(&pad(&prod.0, rows), r_x) == *v)
+    }
+}
+
+/// ccs structure with committed relaxed instance (Ē, u, W̄, x) and witness shared with relaxed r1cs
+/// homogenized as Σ ci · u^(d - |Si|) · ◦_{j ∈ Si} Mj · Z = E so that folding keeps degree d
+#[derive(Clone, Debug)]
+pub struct RelaxedCcs<C: TwistedEdwardsAffine> {
+    pub(crate) ccs: CcsStructure<C>,
+    pub(crate) instance: RelaxedInstance<C>,
+    pub(crate) witness: RelaxedWitness<C>,
+}
+
+impl<C: TwistedEdwardsAffine> RelaxedCcs<C> {
+    /// trivial running instance with u = 0 and zero witness of length n
+    pub fn init(ccs: &CcsStructure<C>, n: usize) -> Self {
+        Self {
+            ccs: ccs.clone(),
+            instance: RelaxedInstance {
+                commit_w: C::ADDITIVE_IDENTITY,
+                commit_e: C::ADDITIVE_IDENTITY,
+                u: C::Scalar::zero(),
+                x: DenseVectors(vec![C::Scalar::zero(); ccs.l]),
+            },
+            witness: RelaxedWitness {
+                w: DenseVectors(vec![C::Scalar::zero(); n]),
+                e: DenseVectors(vec![C::Scalar::zero(); ccs.m]),
+                r_w: C::Scalar::zero(),
+                r_e: C::Scalar::zero(),
+            },
+        }
+    }
+
+    /// committed relaxed instance (Ē, u, W̄, x)
+    pub fn instance(&self) -> &RelaxedInstance<C> {
+        &self.instance
+    }
+
+    /// relaxed witness (E, rE, W, rW)
+    pub fn witness(&self) -> &RelaxedWitness<C> {
+        &self.witness
+    }
+
+    /// check Σ ci · u^(d - |Si|) · ◦_{j ∈ Si} Mj · Z = E
+    pub fn is_sat(&self) -> bool {
+        let RelaxedInstance { u, x, .. } = &self.instance;
+        let prods = self.ccs.prods(*u, x, &self.witness.w);
+        (0..self.ccs.m).all(|i| {
+            let v = prods.iter().map(|prod| prod[i]).collect::<Vec<_>>();
+            self.ccs.combine_relaxed(*u, &v) == self.witness.e[i]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cccs, CcsStructure};
+    use crate::error::Error;
+    use crate::public_param::PedersenCommitment;
+    use crate::tests::{example_r1cs, example_r1cs_witness};
+    use crate::wire::Wire;
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use rand_core::OsRng;
+    use zkstd::common::PrimeField;
+
+    #[test]
+    fn r1cs_to_ccs_test() {
+        let r1cs = example_r1cs::<Curve>();
+        let ccs = CcsStructure::from(r1cs.clone());
+        let pp = PedersenCommitment::<Curve>::from_r1cs(&r1cs, OsRng);
+
+        assert_eq!((ccs.m(), ccs.l()), (r1cs.m(), r1cs.l()));
+        assert_eq!((ccs.t(), ccs.q(), ccs.degree()), (3, 2, 2));
+        for i in 0..5 {
+            let z = example_r1cs_witness(i);
+            assert!(Cccs::new(&ccs, &z, &pp, OsRng).unwrap().is_sat());
+
+            let mut invalid_z = z.clone();
+            invalid_z[0] += Scalar::one();
+            assert!(!Cccs::new(&ccs, &invalid_z, &pp, OsRng).unwrap().is_sat());
+        }
+
+        let z = example_r1cs_witness(3);
+        assert_eq!(
+            Cccs::new(&ccs, &z[..ccs.l()], &pp, OsRng).err(),
+            Some(Error::InvalidLength)
+        );
+        assert_eq!(
+            Cccs::new(&ccs, &[z, vec![Scalar::one()]].concat(), &pp, OsRng).err(),
+            Some(Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn custom_gate_test() {
+        // x^5 = y in one constraint of degree 5 instead of three r1cs constraints
+        let (x, y) = (Wire::witness(0), Wire::instance(0));
+        let one = Scalar::one();
+        let ccs = CcsStructure::<Curve>::new(
+            1,
+            1,
+            vec![vec![vec![(x, one)]], vec![vec![(y, one)]]],
+            vec![vec![0; 5], vec![1]],
+            vec![one, Scalar::zero() - one],
+        );
+        let pp = PedersenCommitment::<Curve>::new(2, OsRng);
+        let value = Scalar::from(3);
+        let z = vec![value.square().square() * value, value];
+        let cccs = Cccs::new(&ccs, &z, &pp, OsRng).unwrap();
+
+        assert_eq!(ccs.degree(), 5);
+        assert!(cccs.is_sat());
+        assert!(cccs.linearize(vec![]).is_sat());
+        assert!(
+            !Cccs::new(&ccs, &[value.square().square(), value], &pp, OsRng)
+                .unwrap()
+                .is_sat()
+        )
+    }
+}
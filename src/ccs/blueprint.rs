@@ -0,0 +1,205 @@
+use super::Gate;
+use crate::matrix::{DenseVectors, Element, SparseMatrix};
+use crate::r1cs::R1csStructure;
+use crate::wire::Wire;
+
+use zkstd::common::{Group, Ring, TwistedEdwardsAffine};
+
+/// sparse matrix as rows of (wire, coeff)
+type Rows<F> = Vec<Vec<(Wire, F)>>;
+
+/// https://eprint.iacr.org/2023/552.pdf
+/// 2 Definition 2.1 CCS
+///  Σ ci · ◦_{j ∈ Si} Mj · Z = 0
+/// r1cs, plonkish and air are special cases selecting matrices and multisets
+#[derive(Clone, Debug)]
+pub struct CcsStructure<C: TwistedEdwardsAffine> {
+    /// matrix length
+    pub(crate) m: usize,
+    /// instance length
+    pub(crate) l: usize,
+    /// matrices M0, .., Mt-1
+    pub(crate) matrices: Vec<SparseMatrix<C::Scalar>>,
+    /// multisets S0, .., Sq-1 of matrix indices
+    pub(crate) multisets: Vec<Vec<usize>>,
+    /// constants c0, .., cq-1
+    pub(crate) constants: Vec<C::Scalar>,
+}
+
+impl<C: TwistedEdwardsAffine> CcsStructure<C> {
+    /// ccs from matrices given as rows of (wire, coeff), multisets of matrix indices and constants
+    pub fn new(
+        m: usize,
+        l: usize,
+        matrices: Vec<Rows<C::Scalar>>,
+        multisets: Vec<Vec<usize>>,
+        constants: Vec<C::Scalar>,
+    ) -> Self {
+        assert_eq!(multisets.len(), constants.len());
+        assert!(multisets.iter().flatten().all(|j| *j < matrices.len()));
+        let matrices = matrices
+            .into_iter()
+            .map(|rows| {
+                assert_eq!(rows.len(), m);
+                let rows = rows
+                    .into_iter()
+                    .map(|row| row.into_iter().map(|(w, c)| Element(w, c)).collect())
+                    .collect();
+                SparseMatrix(rows)
+            })
+            .collect();
+        Self {
+            m,
+            l,
+            matrices,
+            multisets,
+            constants,
+        }
+    }
+
+    /// ccs with one row for each gate
+    /// term k of all gates shares multiset Sk whose size is max number of factors of term k
+    /// so missing factors are padded with constant wire and coeffs go into first factor
+    pub fn from_gates(l: usize, gates: &[Gate<C::Scalar>]) -> Self {
+        let one = C::Scalar::one();
+        let mut sizes = vec![];
+        gates.iter().for_each(|gate| {
+            gate.terms.iter().enumerate().for_each(|(k, (_, factors))| {
+                if k == sizes.len() {
+                    sizes.push(0)
+                }
+                sizes[k] = sizes[k].max(factors.len())
+            })
+        });
+        let multisets = sizes
+            .iter()
+            .scan(0, |offset, size| {
+                let multiset = (*offset..*offset + size).collect::<Vec<_>>();
+                *offset += size;
+                Some(multiset)
+            })
+            .collect::<Vec<_>>();
+
+        let mut matrices = vec![SparseMatrix(vec![vec![]; gates.len()]); sizes.iter().sum()];
+        gates.iter().enumerate().for_each(|(row, gate)| {
+            gate.terms
+                .iter()
+                .zip(multisets.iter())
+                .for_each(|((coeff, factors), multiset)| {
+                    multiset.iter().enumerate().for_each(|(p, j)| {
+                        matrices[*j].0[row] = match factors.get(p) {
+                            Some(factor) => factor
+                                .0
+                                .iter()
+                                .map(|Element(wire, c)| {
+                                    let c = if p == 0 { *coeff * *c } else { *c };
+                                    Element(*wire, c)
+                                })
+                                .collect(),
+                            None => vec![Element(Wire::One, one)],
+                        }
+                    })
+                })
+        });
+        Self {
+            m: gates.len(),
+            l,
+            matrices,
+            constants: vec![one; multisets.len()],
+            multisets,
+        }
+    }
+
+    /// number of constraints
+    pub fn m(&self) -> usize {
+        self.m
+    }
+
+    /// instance length
+    pub fn l(&self) -> usize {
+        self.l
+    }
+
+    /// witness length as largest witness wire used by matrices
+    pub fn witness_len(&self) -> usize {
+        self.matrices
+            .iter()
+            .flat_map(|matrix| matrix.0.iter().flatten())
+            .fold(0, |len, element| match element.0 {
+                Wire::Witness(i) => len.max(i + 1),
+                _ => len,
+            })
+    }
+
+    /// number of matrices
+    pub fn t(&self) -> usize {
+        self.matrices.len()
+    }
+
+    /// number of multisets
+    pub fn q(&self) -> usize {
+        self.multisets.len()
+    }
+
+    /// degree d = max |Si|
+    pub fn degree(&self) -> usize {
+        self.multisets.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// Mj · Z for each matrix where u is assigned to constant wire
+    pub(crate) fn prods(
+        &self,
+        u: C::Scalar,
+        x: &DenseVectors<C::Scalar>,
+        w: &DenseVectors<C::Scalar>,
+    ) -> Vec<DenseVectors<C::Scalar>> {
+        self.matrices
+            .iter()
+            .map(|matrix| matrix.prod(self.m, u, x, w))
+            .collect()
+    }
+
+    /// Σ ci · Π_{j ∈ Si} vj for evaluations vj of Mj · Z
+    pub(crate) fn combine(&self, v: &[C::Scalar]) -> C::Scalar {
+        self.multisets
+            .iter()
+            .zip(self.constants.iter())
+            .fold(C::Scalar::zero(), |sum, (multiset, c)| {
+                sum + multiset.iter().fold(*c, |prod, j| prod * v[*j])
+            })
+    }
+
+    /// Σ ci · u^(d - |Si|) · Π_{j ∈ Si} vj homogenized by u to degree d
+    pub(crate) fn combine_relaxed(&self, u: C::Scalar, v: &[C::Scalar]) -> C::Scalar {
+        let d = self.degree();
+        self.multisets.iter().zip(self.constants.iter()).fold(
+            C::Scalar::zero(),
+            |sum, (multiset, c)| {
+                let c = (multiset.len()..d).fold(*c, |c, _| c * u);
+                sum + multiset.iter().fold(c, |prod, j| prod * v[*j])
+            },
+        )
+    }
+}
+
+/// r1cs as ccs with t = 3, q = 2, d = 2, S0 = {0, 1}, S1 = {2} and c = (1, -1)
+impl<C: TwistedEdwardsAffine> From<R1csStructure<C>> for CcsStructure<C> {
+    fn from(r1cs: R1csStructure<C>) -> Self {
+        let R1csStructure { m, l, a, b, c } = r1cs;
+        let matrices = [a, b, c]
+            .into_iter()
+            .map(|mut matrix| {
+                // structure may hold empty row for next constraint
+                matrix.0.resize(m, vec![]);
+                matrix
+            })
+            .collect();
+        Self {
+            m,
+            l,
+            matrices,
+            multisets: vec![vec![0, 1], vec![2]],
+            constants: vec![C::Scalar::one(), C::Scalar::zero() - C::Scalar::one()],
+        }
+    }
+}
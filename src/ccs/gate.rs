@@ -0,0 +1,36 @@
+use crate::linear_combination::LinearCombination;
+
+use zkstd::common::PrimeField;
+
+/// coeff and factors of term where each factor is linear combination of wires
+type Term<F> = (F, Vec<LinearCombination<F>>);
+
+/// custom gate Σ ci · Π lcij = 0 over linear combinations lcij of wires
+/// e.g. x^5 - y = 0 for poseidon s-box is single gate of degree 5
+#[derive(Clone, Debug, Default)]
+pub struct Gate<F: PrimeField> {
+    pub(crate) terms: Vec<Term<F>>,
+}
+
+impl<F: PrimeField> Gate<F> {
+    /// gate without terms
+    pub fn new() -> Self {
+        Self { terms: vec![] }
+    }
+
+    /// add term coeff · Π factors where each factor is linear combination Σ coeff · wire
+    pub fn term(mut self, coeff: F, factors: Vec<LinearCombination<F>>) -> Self {
+        assert!(!factors.is_empty(), "term requires at least one factor");
+        self.terms.push((coeff, factors));
+        self
+    }
+
+    /// degree of gate
+    pub fn degree(&self) -> usize {
+        self.terms
+            .iter()
+            .map(|(_, factors)| factors.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
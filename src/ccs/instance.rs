@@ -0,0 +1,116 @@
+use super::CcsStructure;
+use crate::matrix::DenseVectors;
+use crate::r1cs::Instance as CccsInstance;
+use crate::transcript::ChallengeTranscript;
+
+use zkstd::common::{CurveGroup, Group, TwistedEdwardsAffine};
+
+/// linearized committed ccs instance (W̄, u, x, rx, v) with vj = M̃j · z̃(rx)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instance<C: TwistedEdwardsAffine> {
+    /// commitment for witness vectors
+    pub(crate) commit_w: C,
+    /// scalar for constant wire
+    pub(crate) u: C::Scalar,
+    /// public inputs and outputs
+    pub(crate) x: DenseVectors<C::Scalar>,
+    /// evaluation point over rows
+    pub(crate) r_x: Vec<C::Scalar>,
+    /// evaluations of Mj · Z at rx
+    pub(crate) v: Vec<C::Scalar>,
+}
+
+impl<C: TwistedEdwardsAffine> Instance<C> {
+    /// trivial instance satisfied by zero witness
+    pub(crate) fn init(ccs: &CcsStructure<C>) -> Self {
+        let rounds = ccs.m.next_power_of_two().trailing_zeros() as usize;
+        Self {
+            commit_w: C::ADDITIVE_IDENTITY,
+            u: C::Scalar::zero(),
+            x: DenseVectors(vec![C::Scalar::zero(); ccs.l]),
+            r_x: vec![C::Scalar::zero(); rounds],
+            v: vec![C::Scalar::zero(); ccs.t()],
+        }
+    }
+
+    /// commitment for witness vectors
+    pub fn commit_w(&self) -> C {
+        self.commit_w
+    }
+
+    /// scalar for constant wire
+    pub fn u(&self) -> C::Scalar {
+        self.u
+    }
+
+    /// public inputs and outputs
+    pub fn x(&self) -> &[C::Scalar] {
+        &self.x.0
+    }
+
+    /// evaluation point over rows
+    pub fn r_x(&self) -> &[C::Scalar] {
+        &self.r_x
+    }
+
+    /// evaluations of Mj · Z at rx
+    pub fn v(&self) -> &[C::Scalar] {
+        &self.v
+    }
+
+    /// absorb (commit_W, u, x, rx, v) into transcript
+    pub(crate) fn absorb<T: ChallengeTranscript<C>>(&self, transcript: &mut T) {
+        transcript.append_point(b"commit_w", &self.commit_w);
+        transcript.append_scalar(b"u", &self.u);
+        self.x
+            .iter()
+            .for_each(|x| transcript.append_scalar(b"x", &x));
+        self.r_x
+            .iter()
+            .for_each(|r| transcript.append_scalar(b"r_x", r));
+        self.v
+            .iter()
+            .for_each(|v| transcript.append_scalar(b"v", v));
+    }
+
+    /// fold committed instances with U' = U + Σ ρ^k · uk at new point rx'
+    /// where v' = σ + Σ ρ^k · θk are evaluations at rx'
+    pub(crate) fn fold(
+        &self,
+        instances: &[CccsInstance<C>],
+        powers: &[C::Scalar],
+        r_x: Vec<C::Scalar>,
+        sigmas: &[C::Scalar],
+        thetas: &[Vec<C::Scalar>],
+    ) -> Self {
+        let init = (
+            C::Extended::ADDITIVE_IDENTITY,
+            self.u,
+            self.x.clone(),
+            sigmas.to_vec(),
+        );
+        let (commit_w, u, x, v) = instances.iter().zip(powers).zip(thetas).fold(
+            init,
+            |(commit_w, u, x, v), ((instance, rho), theta)| {
+                let v = v
+                    .iter()
+                    .zip(theta.iter())
+                    .map(|(v, theta)| *v + *rho * *theta)
+                    .collect();
+                (
+                    commit_w + instance.commit_w * rho,
+                    u + *rho,
+                    x + instance.x.clone() * *rho,
+                    v,
+                )
+            },
+        );
+        Self {
+            commit_w: (commit_w + self.commit_w).into(),
+            u,
+            x,
+            r_x,
+            v,
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::matrix::DenseVectors;
+use crate::r1cs::Witness as CccsWitness;
+
+use zkstd::common::{Group, TwistedEdwardsAffine};
+
+/// linearized committed ccs witness (W, rW)
+#[derive(Clone, Debug)]
+pub struct Witness<C: TwistedEdwardsAffine> {
+    /// intermediate value and private inputs
+    pub(crate) w: DenseVectors<C::Scalar>,
+    /// randomness for witness commitment
+    pub(crate) r_w: C::Scalar,
+}
+
+impl<C: TwistedEdwardsAffine> Witness<C> {
+    /// zero witness of length n
+    pub(crate) fn init(n: usize) -> Self {
+        Self {
+            w: DenseVectors(vec![C::Scalar::zero(); n]),
+            r_w: C::Scalar::zero(),
+        }
+    }
+
+    /// intermediate value and private inputs
+    pub fn w(&self) -> &[C::Scalar] {
+        &self.w.0
+    }
+
+    /// fold witnesses with W' = W + Σ ρ^k · wk
+    pub(crate) fn fold(&self, witnesses: &[CccsWitness<C>], powers: &[C::Scalar]) -> Self {
+        witnesses
+            .iter()
+            .zip(powers)
+            .fold(self.clone(), |Self { w, r_w }, (witness, rho)| Self {
+                w: w + witness.w.clone() * *rho,
+                r_w: r_w + witness.r_w * *rho,
+            })
+    }
+}
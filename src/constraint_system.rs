@@ -1,72 +1,343 @@
-use crate::matrix::Element;
-use crate::r1cs::R1csInstance;
+mod namespace;
+mod stats;
+
+pub use namespace::Namespace;
+pub use stats::CircuitStats;
+
+use crate::error::Error;
+use crate::linear_combination::LinearCombination;
+use crate::matrix::{Element, SparseMatrix};
+use crate::public_param::PedersenCommitment;
+use crate::r1cs::{Instance, R1csInstance, R1csStructure, UnsatisfiedConstraint, Witness};
 use crate::wire::Wire;
 
-use zkstd::common::{Ring, TwistedEdwardsAffine};
+use std::collections::HashMap;
+use zkstd::common::{Group, RngCore, TwistedEdwardsAffine};
+
+/// setup records shape with unknown values and prove assigns values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Setup,
+    Prove,
+}
 
 #[derive(Debug)]
 pub struct ConstraintSystem<C: TwistedEdwardsAffine> {
     r1cs: R1csInstance<C>,
+    mode: Mode,
+    /// namespaces entered so far
+    path: Vec<String>,
+    /// interned index of current path
+    current_path: Option<usize>,
+    /// unique namespace paths which constraints were created in
+    paths: Vec<String>,
+    /// index of each path in paths
+    path_indices: HashMap<String, usize>,
+    /// interned namespace path of each constraint kept out of structure
+    namespaces: Vec<Option<usize>>,
+    /// label of each constraint kept out of structure
+    constraint_labels: Vec<Option<String>>,
+    /// labels of wires qualified by namespace
+    wire_labels: HashMap<Wire, String>,
+}
+
+impl<C: TwistedEdwardsAffine> Default for ConstraintSystem<C> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<C: TwistedEdwardsAffine> ConstraintSystem<C> {
-    /// init constraint system with first instance one
+    /// init constraint system in proving mode with first instance one
     pub fn new() -> Self {
         Self {
             r1cs: R1csInstance::default(),
+            mode: Mode::Prove,
+            path: vec![],
+            current_path: None,
+            paths: vec![],
+            path_indices: HashMap::new(),
+            namespaces: vec![],
+            constraint_labels: vec![],
+            wire_labels: HashMap::new(),
+        }
+    }
+
+    /// init constraint system in setup mode which records constraints without values
+    /// so that structure is available before any input exists
+    pub fn setup() -> Self {
+        Self {
+            r1cs: R1csInstance::default(),
+            mode: Mode::Setup,
+            path: vec![],
+            current_path: None,
+            paths: vec![],
+            path_indices: HashMap::new(),
+            namespaces: vec![],
+            constraint_labels: vec![],
+            wire_labels: HashMap::new(),
+        }
+    }
+
+    /// whether values are unknown and assigned as zero
+    pub fn is_setup(&self) -> bool {
+        self.mode == Mode::Setup
+    }
+
+    /// enter namespace until returned guard is dropped
+    /// path like "sha256/round_3" enters each segment
+    pub fn namespace(&mut self, path: &str) -> Namespace<'_, C> {
+        let len = self.path.len();
+        self.path.extend(
+            path.split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(String::from),
+        );
+        let depth = self.path.len() - len;
+        self.intern_path();
+        Namespace { cs: self, depth }
+    }
+
+    /// label wire under current namespace
+    pub fn label_wire(&mut self, wire: Wire, label: &str) {
+        let label = self.qualify(label);
+        self.wire_labels.insert(wire, label);
+    }
+
+    /// label of wire with namespace it was labeled in
+    pub fn wire_label(&self, wire: Wire) -> Option<&str> {
+        self.wire_labels.get(&wire).map(String::as_str)
+    }
+
+    /// label last added constraint
+    pub fn label_constraint(&mut self, label: &str) {
+        if let Some(last) = self.constraint_labels.last_mut() {
+            *last = Some(label.to_string())
         }
     }
 
+    /// number of wires and constraints per namespace
+    pub fn stats(&self) -> CircuitStats {
+        CircuitStats::new(
+            self.r1cs.witness.public_len(),
+            self.r1cs.witness.private_len(),
+            self.namespaces
+                .iter()
+                .map(|path| path.map(|path| self.paths[path].as_str())),
+        )
+    }
+
     /// assign instance value to constraint system
     pub fn public_wire(&mut self, instance: C::Scalar) -> Wire {
-        let index = self.r1cs.witness.public_len();
-        self.r1cs.witness.append_instance(instance);
-        Wire::instance(index)
+        self.alloc_public(|| instance)
     }
 
     /// assign witness value to constraint system
     pub fn private_wire(&mut self, witness: C::Scalar) -> Wire {
+        self.alloc_private(|| witness)
+    }
+
+    /// allocate instance whose value is computed only in proving mode
+    pub fn alloc_public(&mut self, value: impl FnOnce() -> C::Scalar) -> Wire {
+        let index = self.r1cs.witness.public_len();
+        let value = self.assign(value);
+        self.r1cs.witness.append_instance(value);
+        self.r1cs.r1cs.l = index + 1;
+        Wire::instance(index)
+    }
+
+    /// allocate witness whose value is computed only in proving mode
+    pub fn alloc_private(&mut self, value: impl FnOnce() -> C::Scalar) -> Wire {
         let index = self.r1cs.witness.private_len();
-        self.r1cs.witness.append_witness(witness);
+        let value = self.assign(value);
+        self.r1cs.witness.append_witness(value);
         Wire::witness(index)
     }
 
+    /// check that constraints synthesized in proving mode have shape recorded at setup
+    pub fn check_shape(&self, shape: &R1csStructure<C>) -> Result<(), Error> {
+        if self.structure().digest() == shape.digest() {
+            Ok(())
+        } else {
+            Err(Error::ShapeMismatch)
+        }
+    }
+
     /// constrain a + b == c
     pub fn add_constraint(&mut self, a: Wire, b: Wire, c: Wire) {
-        self.r1cs.r1cs.append_a(a);
-        self.enable_constraint(b, C::Scalar::one(), c)
+        self.enforce(LinearCombination::from(a) + b, Wire::One, c)
     }
 
     /// constrain a * b == c
     pub fn mul_constraint(&mut self, a: Wire, b: Wire, c: Wire) {
-        self.enable_constraint(a, b, c)
+        self.enforce(a, b, c)
     }
 
     /// constrain a == b
     pub fn equal_constraint(&mut self, a: Wire, b: Wire) {
-        self.enable_constraint(a, C::Scalar::one(), b)
+        self.enforce(a, Wire::One, b)
     }
 
-    /// add constraint internally
-    fn enable_constraint(
+    /// constrain (Σ a) * (Σ b) == Σ c over linear combinations
+    pub fn enforce(
         &mut self,
-        a: impl Into<Element<C::Scalar>>,
-        b: impl Into<Element<C::Scalar>>,
-        c: impl Into<Element<C::Scalar>>,
+        a: impl Into<LinearCombination<C::Scalar>>,
+        b: impl Into<LinearCombination<C::Scalar>>,
+        c: impl Into<LinearCombination<C::Scalar>>,
     ) {
-        self.r1cs.r1cs.append(a, b, c);
-        self.r1cs.r1cs.increment()
+        self.constrain(a.into().0, b.into().0, c.into().0)
+    }
+
+    /// value of linear combination with assigned values
+    pub fn evaluate(&self, lc: &LinearCombination<C::Scalar>) -> C::Scalar {
+        lc.0.iter().fold(C::Scalar::zero(), |sum, element| {
+            let (wire, coeff) = element.get();
+            sum + self.value(wire) * coeff
+        })
+    }
+
+    /// value assigned to wire
+    pub fn value(&self, wire: Wire) -> C::Scalar {
+        match wire {
+            Wire::Instance(index) => self.r1cs.witness.x[index],
+            Wire::Witness(index) => self.r1cs.witness.w[index],
+            Wire::One => self.r1cs.witness.one,
+        }
+    }
+
+    /// constrain (Σ a) * (Σ b) == Σ c
+    pub(crate) fn constrain(
+        &mut self,
+        a: Vec<Element<C::Scalar>>,
+        b: Vec<Element<C::Scalar>>,
+        c: Vec<Element<C::Scalar>>,
+    ) {
+        self.r1cs.r1cs.extend(a, b, c);
+        self.r1cs.r1cs.increment();
+        self.namespaces.push(self.current_path);
+        self.constraint_labels.push(None)
+    }
+
+    /// intern current path after entering or leaving namespace
+    fn intern_path(&mut self) {
+        self.current_path = (!self.path.is_empty()).then(|| {
+            let path = self.path.join("/");
+            match self.path_indices.get(&path) {
+                Some(index) => *index,
+                None => {
+                    self.paths.push(path.clone());
+                    self.path_indices.insert(path, self.paths.len() - 1);
+                    self.paths.len() - 1
+                }
+            }
+        })
+    }
+
+    /// label prefixed by current namespace
+    fn qualify(&self, label: &str) -> String {
+        self.path
+            .iter()
+            .map(String::as_str)
+            .chain([label])
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn assign(&self, value: impl FnOnce() -> C::Scalar) -> C::Scalar {
+        match self.mode {
+            Mode::Setup => C::Scalar::zero(),
+            Mode::Prove => value(),
+        }
+    }
+
+    /// r1cs structure of constraints with public wires as instance
+    pub fn structure(&self) -> R1csStructure<C> {
+        let R1csStructure { m, l: _, a, b, c } = self.r1cs.r1cs.clone();
+        let trim = |matrix: SparseMatrix<C::Scalar>| SparseMatrix(matrix.0[..m].to_vec());
+        R1csStructure {
+            m,
+            l: self.r1cs.witness.public_len(),
+            a: trim(a),
+            b: trim(b),
+            c: trim(c),
+        }
+    }
+
+    /// finish circuit into structure with l public wires and committed instance and witness
+    pub fn finalize(
+        self,
+        pp: &PedersenCommitment<C>,
+        rng: impl RngCore,
+    ) -> (R1csStructure<C>, Instance<C>, Witness<C>) {
+        let structure = self.structure();
+        let mut witness = self.r1cs.witness;
+        let commit_w = witness.commit(pp, rng);
+        let instance = Instance {
+            commit_w,
+            x: witness.x.clone(),
+        };
+        (structure, instance, witness)
+    }
+
+    /// finish circuit into r1cs for `Prover::prove`
+    pub fn into_r1cs(self, pp: &PedersenCommitment<C>, rng: impl RngCore) -> R1csInstance<C> {
+        let (r1cs, instance, witness) = self.finalize(pp, rng);
+        R1csInstance {
+            r1cs,
+            instance,
+            witness,
+        }
+    }
+
+    /// assignment z = (x, W) for structure
+    pub fn assignment(&self) -> Vec<C::Scalar> {
+        let (x, w) = self.r1cs.witness.get();
+        x.iter().chain(w.iter()).collect()
     }
 
     /// check whether constraints satisfy
     pub fn is_sat(&self) -> bool {
         self.r1cs.is_sat()
     }
+
+    /// failing constraints with evaluated rows, labels of constraints and wires in rows and
+    /// namespaces they were created in
+    pub fn unsatisfied(&self) -> Vec<UnsatisfiedConstraint<C::Scalar>> {
+        let R1csStructure { a, b, c, .. } = &self.r1cs.r1cs;
+        self.r1cs
+            .unsatisfied()
+            .into_iter()
+            .map(|mut constraint| {
+                let index = constraint.index;
+                constraint.namespace = self.namespaces[index].map(|path| self.paths[path].clone());
+                constraint.label = self.constraint_labels[index].clone();
+                a[index]
+                    .iter()
+                    .chain(b[index].iter())
+                    .chain(c[index].iter())
+                    .for_each(|Element(wire, _)| {
+                        if let Some(label) = self.wire_labels.get(wire) {
+                            if constraint
+                                .wire_labels
+                                .iter()
+                                .all(|(labeled, _)| labeled != wire)
+                            {
+                                constraint.wire_labels.push((*wire, label.clone()))
+                            }
+                        }
+                    });
+                constraint
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::ConstraintSystem;
+    use crate::error::Error;
+    use crate::linear_combination::LinearCombination;
+    use crate::wire::Wire;
 
     use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
     use zkstd::common::PrimeField;
@@ -95,6 +366,207 @@ mod tests {
         assert!(cs.is_sat())
     }
 
+    #[test]
+    fn setup_test() {
+        // x^3 + x + 5 = y
+        let circuit = |cs: &mut ConstraintSystem<Curve>, x: Option<Scalar>| {
+            // values are unwrapped only in proving mode
+            let five = Scalar::from(5);
+            let a = cs.alloc_private(|| x.unwrap());
+            let b = cs.alloc_private(|| x.unwrap().square());
+            let c = cs.alloc_private(|| x.unwrap().square() * x.unwrap());
+            let y = cs.alloc_public(|| x.unwrap().square() * x.unwrap() + x.unwrap() + five);
+            cs.mul_constraint(a, a, b);
+            cs.mul_constraint(b, a, c);
+            cs.enforce(
+                LinearCombination::from(c) + a + LinearCombination::constant(five),
+                Wire::One,
+                y,
+            );
+        };
+
+        let mut cs = ConstraintSystem::setup();
+        circuit(&mut cs, None);
+        let shape = cs.structure();
+        assert!(cs.is_setup());
+        assert_eq!((shape.m(), shape.l()), (3, 1));
+        assert!(cs.assignment().iter().all(|v| *v == Scalar::zero()));
+
+        let mut cs = ConstraintSystem::new();
+        circuit(&mut cs, Some(Scalar::from(3)));
+        assert!(!cs.is_setup());
+        assert!(cs.is_sat());
+        assert_eq!(cs.check_shape(&shape), Ok(()));
+
+        cs.equal_constraint(Wire::witness(0), Wire::witness(0));
+        assert_eq!(cs.check_shape(&shape), Err(Error::ShapeMismatch))
+    }
+
+    #[test]
+    fn unsatisfied_test() {
+        let (x, y) = (Scalar::from(3), Scalar::from(5));
+
+        let mut cs = ConstraintSystem::<Curve>::new();
+        let (a, b) = (cs.private_wire(x), cs.private_wire(y));
+        let square = cs.public_wire(x.square());
+        cs.mul_constraint(a, a, square);
+        {
+            let mut outer = cs.namespace("outer");
+            outer.equal_constraint(a, a);
+            let mut inner = outer.namespace("inner");
+            let c = inner.public_wire(x * y + Scalar::one());
+            inner.mul_constraint(a, b, c);
+        }
+        let d = cs.public_wire(x);
+        cs.equal_constraint(b, d);
+
+        let unsatisfied = cs.unsatisfied();
+        assert!(!cs.is_sat());
+        assert_eq!(unsatisfied.len(), 2);
+        assert_eq!(
+            (unsatisfied[0].index(), unsatisfied[0].namespace()),
+            (2, Some("outer/inner"))
+        );
+        assert_eq!(
+            (unsatisfied[0].a(), unsatisfied[0].b(), unsatisfied[0].c()),
+            (x, y, x * y + Scalar::one())
+        );
+        assert_eq!(
+            (unsatisfied[1].index(), unsatisfied[1].namespace()),
+            (3, None)
+        );
+        assert_eq!(
+            unsatisfied[1].to_string(),
+            format!(
+                "constraint 3 unsatisfied: A · z = {:?}, B · z = {:?}, C · z = {:?}",
+                y,
+                Scalar::one(),
+                x
+            )
+        );
+    }
+
+    #[test]
+    fn namespace_test() {
+        let x = Scalar::from(3);
+        let circuit = |cs: &mut ConstraintSystem<Curve>, label: bool| {
+            let a = cs.alloc_private(|| x);
+            let mut sha256 = cs.namespace("sha256/round_3");
+            let b = sha256.alloc_public(|| x.square());
+            sha256.mul_constraint(a, a, b);
+            {
+                let mut sigma = sha256.namespace("sigma");
+                let c = sigma.alloc_private(|| x + Scalar::one());
+                sigma.add_constraint(a, Wire::One, c);
+                if label {
+                    sigma.label_wire(a, "a");
+                    sigma.label_wire(c, "c");
+                    sigma.label_constraint("increment");
+                }
+            }
+            sha256.equal_constraint(a, b);
+            if label {
+                sha256.label_wire(b, "b");
+                sha256.label_constraint("a == b");
+            }
+        };
+
+        let mut cs = ConstraintSystem::<Curve>::new();
+        circuit(&mut cs, true);
+        let mut shape = ConstraintSystem::<Curve>::setup();
+        circuit(&mut shape, false);
+        let stats = cs.stats();
+        let unsatisfied = cs.unsatisfied();
+
+        // labels are kept out of structure
+        assert_eq!(cs.check_shape(&shape.structure()), Ok(()));
+        assert_eq!(
+            cs.wire_label(Wire::witness(1)),
+            Some("sha256/round_3/sigma/c")
+        );
+        assert_eq!(cs.wire_label(Wire::One), None);
+        assert_eq!(unsatisfied.len(), 1);
+        assert_eq!(
+            (unsatisfied[0].namespace(), unsatisfied[0].label()),
+            (Some("sha256/round_3"), Some("a == b"))
+        );
+        assert_eq!(
+            unsatisfied[0].wire_labels(),
+            &[
+                (Wire::witness(0), "sha256/round_3/sigma/a".to_string()),
+                (Wire::instance(0), "sha256/round_3/b".to_string())
+            ]
+        );
+        assert!(unsatisfied[0]
+            .to_string()
+            .starts_with("constraint 2 (a == b) in sha256/round_3 unsatisfied"));
+        assert!(unsatisfied[0]
+            .to_string()
+            .ends_with("over wires sha256/round_3/sigma/a, sha256/round_3/b"));
+        assert_eq!(
+            (
+                stats.constraints(),
+                stats.public_wires(),
+                stats.private_wires()
+            ),
+            (3, 1, 2)
+        );
+        assert_eq!(
+            stats.namespaces().collect::<Vec<_>>(),
+            vec![
+                ("sha256", 3),
+                ("sha256/round_3", 3),
+                ("sha256/round_3/sigma", 1)
+            ]
+        );
+        assert_eq!(stats.namespace("other"), 0);
+        assert_eq!(
+            stats.to_string(),
+            "3 constraints, 1 public wires, 2 private wires\nsha256: 3\n  round_3: 3\n    sigma: 1\n"
+        );
+    }
+
+    #[test]
+    fn namespace_order_test() {
+        let mut cs = ConstraintSystem::<Curve>::new();
+        let a = cs.alloc_private(Scalar::one);
+        cs.namespace("sha256-x").equal_constraint(a, a);
+        cs.namespace("sha256/round_3").equal_constraint(a, a);
+        cs.namespace("sha256").equal_constraint(a, a);
+        let stats = cs.stats();
+
+        // "-" sorts before "/" but children of sha256 come before its sibling sha256-x
+        assert_eq!(
+            stats.namespaces().collect::<Vec<_>>(),
+            vec![("sha256", 2), ("sha256/round_3", 1), ("sha256-x", 1)]
+        );
+        assert_eq!(
+            stats.to_string(),
+            "3 constraints, 0 public wires, 1 private wires\nsha256: 2\n  round_3: 1\nsha256-x: 1\n"
+        );
+    }
+
+    #[test]
+    fn enforce_test() {
+        // 3x - 2y + 7 = z in one constraint without intermediate wires
+        let (x, y) = (Scalar::from(5), Scalar::from(4));
+        let (two, three, seven) = (Scalar::from(2), Scalar::from(3), Scalar::from(7));
+
+        let mut cs = ConstraintSystem::<Curve>::new();
+        let (a, b) = (cs.private_wire(x), cs.private_wire(y));
+        let lc = a * three - b * two + LinearCombination::constant(seven);
+        let c = cs.public_wire(cs.evaluate(&lc));
+        cs.enforce(lc.clone(), Wire::One, c);
+
+        assert_eq!(cs.value(c), Scalar::from(14));
+        assert_eq!(cs.structure().m(), 1);
+        assert!(cs.is_sat());
+
+        let d = cs.public_wire(Scalar::from(15));
+        cs.enforce(lc, Wire::One, d);
+        assert!(!cs.is_sat())
+    }
+
     #[test]
     fn arithmetic_constraint_test() {
         // R1CS for: x^3 + x + 5 = 35
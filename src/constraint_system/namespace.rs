@@ -0,0 +1,33 @@
+use super::ConstraintSystem;
+
+use std::ops::{Deref, DerefMut};
+use zkstd::common::TwistedEdwardsAffine;
+
+/// constraint system inside namespace whose segments are left on drop
+pub struct Namespace<'a, C: TwistedEdwardsAffine> {
+    pub(super) cs: &'a mut ConstraintSystem<C>,
+    /// number of path segments entered
+    pub(super) depth: usize,
+}
+
+impl<'a, C: TwistedEdwardsAffine> Deref for Namespace<'a, C> {
+    type Target = ConstraintSystem<C>;
+
+    fn deref(&self) -> &ConstraintSystem<C> {
+        self.cs
+    }
+}
+
+impl<'a, C: TwistedEdwardsAffine> DerefMut for Namespace<'a, C> {
+    fn deref_mut(&mut self) -> &mut ConstraintSystem<C> {
+        self.cs
+    }
+}
+
+impl<'a, C: TwistedEdwardsAffine> Drop for Namespace<'a, C> {
+    fn drop(&mut self) {
+        let len = self.cs.path.len() - self.depth;
+        self.cs.path.truncate(len);
+        self.cs.intern_path()
+    }
+}
@@ -0,0 +1,86 @@
+use core::fmt;
+use std::collections::BTreeMap;
+
+/// number of wires and constraints with constraints counted under every namespace prefix
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CircuitStats {
+    pub(crate) constraints: usize,
+    pub(crate) public_wires: usize,
+    pub(crate) private_wires: usize,
+    pub(crate) namespaces: BTreeMap<String, usize>,
+}
+
+impl CircuitStats {
+    /// count constraints of each namespace into its ancestors as well
+    pub(crate) fn new<'a>(
+        public_wires: usize,
+        private_wires: usize,
+        namespaces: impl Iterator<Item = Option<&'a str>>,
+    ) -> Self {
+        let mut stats = Self {
+            public_wires,
+            private_wires,
+            ..Default::default()
+        };
+        namespaces.for_each(|namespace| {
+            stats.constraints += 1;
+            if let Some(namespace) = namespace {
+                namespace
+                    .match_indices('/')
+                    .map(|(i, _)| &namespace[..i])
+                    .chain([namespace])
+                    .for_each(|prefix| {
+                        *stats.namespaces.entry(prefix.to_string()).or_default() += 1
+                    })
+            }
+        });
+        stats
+    }
+
+    /// number of constraints
+    pub fn constraints(&self) -> usize {
+        self.constraints
+    }
+
+    /// number of public wires
+    pub fn public_wires(&self) -> usize {
+        self.public_wires
+    }
+
+    /// number of private wires
+    pub fn private_wires(&self) -> usize {
+        self.private_wires
+    }
+
+    /// constraints created in namespace including nested ones
+    pub fn namespace(&self, namespace: &str) -> usize {
+        self.namespaces.get(namespace).copied().unwrap_or_default()
+    }
+
+    /// namespaces and their constraints in path order comparing segments between '/'
+    /// so that every namespace is followed by its children before siblings like "a-b" of "a"
+    pub fn namespaces(&self) -> impl Iterator<Item = (&str, usize)> {
+        let mut namespaces = self
+            .namespaces
+            .iter()
+            .map(|(namespace, count)| (namespace.as_str(), *count))
+            .collect::<Vec<_>>();
+        namespaces.sort_by(|(a, _), (b, _)| a.split('/').cmp(b.split('/')));
+        namespaces.into_iter()
+    }
+}
+
+impl fmt::Display for CircuitStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} constraints, {} public wires, {} private wires",
+            self.constraints, self.public_wires, self.private_wires
+        )?;
+        self.namespaces().try_for_each(|(namespace, count)| {
+            let depth = namespace.matches('/').count();
+            let name = namespace.rsplit('/').next().unwrap_or(namespace);
+            writeln!(f, "{}{name}: {count}", "  ".repeat(depth))
+        })
+    }
+}
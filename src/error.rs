@@ -0,0 +1,49 @@
+use core::fmt;
+
+/// error for ivc proof verification
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// public io hash of strict instance doesn't match (i, z0, zi, U)
+    InvalidIoHash,
+    /// strict instance is not u = 1 and E = 0
+    InvalidStrictInstance,
+    /// z0 and zi differ at base case
+    InvalidBaseCase,
+    /// commitments don't open to witnesses
+    InvalidCommitment,
+    /// relaxed r1cs is not satisfied
+    UnsatisfiedRelaxedR1cs,
+    /// r1cs is not satisfied
+    UnsatisfiedR1cs,
+    /// sumcheck of compressed proof is not satisfied
+    InvalidSumcheck,
+    /// inner product argument doesn't open commitment to claimed evaluation
+    InvalidOpening,
+    /// circuit index or number of running instances doesn't match step circuits
+    InvalidCircuitIndex,
+    /// number of cross term commitments doesn't match degree of gates or folded instances
+    InvalidCrossTerms,
+    /// constraint system shape differs from structure recorded at setup
+    ShapeMismatch,
+    /// instance or witness length doesn't match structure
+    InvalidLength,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidIoHash => write!(f, "public io hash mismatch"),
+            Self::InvalidStrictInstance => write!(f, "strict instance is not u = 1 and E = 0"),
+            Self::InvalidBaseCase => write!(f, "z0 and zi differ at base case"),
+            Self::InvalidCommitment => write!(f, "commitment opening mismatch"),
+            Self::UnsatisfiedRelaxedR1cs => write!(f, "relaxed r1cs is not satisfied"),
+            Self::UnsatisfiedR1cs => write!(f, "r1cs is not satisfied"),
+            Self::InvalidSumcheck => write!(f, "sumcheck verification failed"),
+            Self::InvalidOpening => write!(f, "commitment opening proof is invalid"),
+            Self::InvalidCircuitIndex => write!(f, "step circuit index is out of range"),
+            Self::InvalidCrossTerms => write!(f, "cross term commitments count mismatch"),
+            Self::ShapeMismatch => write!(f, "constraint system shape mismatch"),
+            Self::InvalidLength => write!(f, "instance or witness length mismatch"),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+mod nonnative;
+
+use crate::constraint_system::ConstraintSystem;
+use crate::hash::{domain, PoseidonConstants, RATE, WIDTH};
+use crate::matrix::Element;
+use crate::wire::Wire;
+
+use zkstd::common::{Group, PrimeField, Ring, TwistedEdwardsAffine};
+
+pub(crate) use nonnative::{
+    add_point, alloc_challenge, alloc_point, constant_point, scalar_mul, select_point,
+    ChallengeWires, PointWires,
+};
+
+/// evaluate Σ coeff · wire with assigned values
+pub(crate) fn evaluate<C: TwistedEdwardsAffine>(
+    cs: &ConstraintSystem<C>,
+    elements: &[Element<C::Scalar>],
+) -> C::Scalar {
+    elements.iter().fold(C::Scalar::zero(), |sum, element| {
+        let (wire, coeff) = element.get();
+        sum + cs.value(wire) * coeff
+    })
+}
+
+/// allocate private wire constrained to Σ coeff · wire
+pub(crate) fn alloc<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    elements: Vec<Element<C::Scalar>>,
+) -> Wire {
+    let value = evaluate(cs, &elements);
+    let wire = cs.private_wire(value);
+    cs.constrain(elements, vec![C::Scalar::one().into()], vec![wire.into()]);
+    wire
+}
+
+/// allocate private wire constrained to a * b
+pub(crate) fn mul<C: TwistedEdwardsAffine>(cs: &mut ConstraintSystem<C>, a: Wire, b: Wire) -> Wire {
+    let value = cs.value(a) * cs.value(b);
+    let wire = cs.private_wire(value);
+    cs.mul_constraint(a, b, wire);
+    wire
+}
+
+/// allocate boolean wire b = 1 if x = 0 otherwise b = 0
+pub(crate) fn is_zero<C: TwistedEdwardsAffine>(cs: &mut ConstraintSystem<C>, x: Wire) -> Wire {
+    let (zero, one) = (C::Scalar::zero(), C::Scalar::one());
+    let value = cs.value(x);
+    let (inv, b) = if value == zero {
+        (zero, one)
+    } else {
+        (value.invert().unwrap(), zero)
+    };
+    let (inv, b) = (cs.private_wire(inv), cs.private_wire(b));
+    // x · inv = 1 - b
+    cs.constrain(
+        vec![x.into()],
+        vec![inv.into()],
+        vec![one.into(), Element(b, zero - one)],
+    );
+    // x · b = 0
+    cs.constrain(vec![x.into()], vec![b.into()], vec![]);
+    b
+}
+
+/// allocate private wire constrained to be boolean
+pub(crate) fn alloc_boolean<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    bit: bool,
+) -> Wire {
+    let value = if bit {
+        C::Scalar::one()
+    } else {
+        C::Scalar::zero()
+    };
+    let b = cs.private_wire(value);
+    // b · b = b
+    cs.mul_constraint(b, b, b);
+    b
+}
+
+/// Σ 2^i · bi for little endian bits
+pub(crate) fn pack_bits<C: TwistedEdwardsAffine>(bits: &[Wire]) -> Vec<Element<C::Scalar>> {
+    bits.iter()
+        .scan(C::Scalar::one(), |coeff, bit| {
+            let element = Element(*bit, *coeff);
+            *coeff = coeff.double();
+            Some(element)
+        })
+        .collect()
+}
+
+/// allocate b ? p : q for boolean b
+pub(crate) fn select<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    b: Wire,
+    p: Wire,
+    q: Wire,
+) -> Wire {
+    let value = if cs.value(b) == C::Scalar::one() {
+        cs.value(p)
+    } else {
+        cs.value(q)
+    };
+    let wire = cs.private_wire(value);
+    let minus_one = C::Scalar::zero() - C::Scalar::one();
+    // b · (p - q) = s - q
+    cs.constrain(
+        vec![b.into()],
+        vec![p.into(), Element(q, minus_one)],
+        vec![wire.into(), Element(q, minus_one)],
+    );
+    wire
+}
+
+/// in circuit poseidon sponge corresponding to `Poseidon`
+/// state is kept as linear combinations and materialized after each linear layer
+pub(crate) struct PoseidonGadget<C: TwistedEdwardsAffine> {
+    state: Vec<Vec<Element<C::Scalar>>>,
+    buffer: Vec<Element<C::Scalar>>,
+    constants: PoseidonConstants<C::Scalar>,
+}
+
+impl<C: TwistedEdwardsAffine> PoseidonGadget<C> {
+    pub(crate) fn new(label: &[u8]) -> Self {
+        let mut state = vec![vec![]; WIDTH];
+        state[0].push(domain::<C::Scalar>(label).into());
+        Self {
+            state,
+            buffer: Vec::with_capacity(RATE),
+            constants: PoseidonConstants::default(),
+        }
+    }
+
+    pub(crate) fn absorb(&mut self, cs: &mut ConstraintSystem<C>, value: Wire) {
+        self.buffer.push(value.into());
+        if self.buffer.len() == RATE {
+            self.absorb_buffer(cs)
+        }
+    }
+
+    /// absorb limbs of coordinates corresponding to `Poseidon::absorb_point`
+    pub(crate) fn absorb_point(&mut self, cs: &mut ConstraintSystem<C>, point: &PointWires) {
+        point
+            .x
+            .iter()
+            .chain(point.y.iter())
+            .for_each(|limb| self.absorb(cs, *limb))
+    }
+
+    /// pad buffer with 1 and zeros and output first rate element
+    pub(crate) fn squeeze(&mut self, cs: &mut ConstraintSystem<C>) -> Wire {
+        self.buffer.push(C::Scalar::one().into());
+        self.absorb_buffer(cs);
+        self.state[1][0].0
+    }
+
+    fn absorb_buffer(&mut self, cs: &mut ConstraintSystem<C>) {
+        self.buffer
+            .drain(..)
+            .zip(self.state.iter_mut().skip(1))
+            .for_each(|(value, state)| state.push(value));
+        self.permute(cs)
+    }
+
+    // s-box constrains t^2, t^4 and t^5 while linear layer allocates each state element
+    fn permute(&mut self, cs: &mut ConstraintSystem<C>) {
+        let PoseidonConstants {
+            round_constants,
+            mds,
+        } = self.constants.clone();
+        round_constants.iter().enumerate().for_each(|(round, c)| {
+            let full = PoseidonConstants::<C::Scalar>::is_full_round(round);
+            let state = self
+                .state
+                .iter()
+                .zip(c.iter())
+                .enumerate()
+                .map(|(i, (t, c))| {
+                    let mut t = t.clone();
+                    t.push((*c).into());
+                    if full || i == 0 {
+                        vec![sbox(cs, t).into()]
+                    } else {
+                        t
+                    }
+                })
+                .collect::<Vec<_>>();
+            self.state = mds
+                .iter()
+                .map(|row| {
+                    let elements = row
+                        .iter()
+                        .zip(state.iter())
+                        .flat_map(|(m, t)| {
+                            t.iter().map(move |Element(wire, c)| Element(*wire, *c * m))
+                        })
+                        .collect();
+                    vec![alloc(cs, elements).into()]
+                })
+                .collect();
+        })
+    }
+}
+
+/// allocate t^5 for linear combination t
+fn sbox<C: TwistedEdwardsAffine>(cs: &mut ConstraintSystem<C>, t: Vec<Element<C::Scalar>>) -> Wire {
+    let value = evaluate(cs, &t);
+    let t2 = cs.private_wire(value.square());
+    cs.constrain(t.clone(), t.clone(), vec![t2.into()]);
+    let t4 = cs.private_wire(value.square().square());
+    cs.constrain(vec![t2.into()], vec![t2.into()], vec![t4.into()]);
+    let t5 = cs.private_wire(value.square().square() * value);
+    cs.constrain(vec![t4.into()], t, vec![t5.into()]);
+    t5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_zero, PoseidonGadget};
+    use crate::constraint_system::ConstraintSystem;
+    use crate::hash::Poseidon;
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+
+    #[test]
+    fn is_zero_test() {
+        for value in [Scalar::zero(), Scalar::one(), Scalar::from(7)] {
+            let mut cs = ConstraintSystem::<Curve>::new();
+            let x = cs.private_wire(value);
+            let b = is_zero(&mut cs, x);
+
+            assert!(cs.is_sat());
+            assert_eq!(cs.value(b) == Scalar::one(), value == Scalar::zero())
+        }
+    }
+
+    #[test]
+    fn poseidon_gadget_test() {
+        for n in 0..5 {
+            let inputs = (0..n).map(|i| Scalar::from(i * 3 + 1)).collect::<Vec<_>>();
+            let mut poseidon = Poseidon::new(b"test");
+            inputs.iter().for_each(|input| poseidon.absorb(*input));
+            let expected = poseidon.squeeze();
+
+            let mut cs = ConstraintSystem::<Curve>::new();
+            let mut gadget = PoseidonGadget::new(b"test");
+            for input in inputs {
+                let wire = cs.private_wire(input);
+                gadget.absorb(&mut cs, wire);
+            }
+            let output = gadget.squeeze(&mut cs);
+
+            assert!(cs.is_sat());
+            assert_eq!(cs.value(output), expected);
+            assert_ne!(expected, Poseidon::new(b"other").squeeze())
+        }
+    }
+}
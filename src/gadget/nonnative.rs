@@ -0,0 +1,487 @@
+use super::{alloc, alloc_boolean, evaluate, mul, pack_bits, select};
+use crate::constraint_system::ConstraintSystem;
+use crate::matrix::Element;
+use crate::nonnative::{
+    cast, challenge_bits, from_limbs, limb_bits, limb_count, log2, modulus_bits, modulus_limbs,
+    power_of_two, quotient, to_bits, to_limbs,
+};
+use crate::wire::Wire;
+
+use zkstd::common::{Group, Ring, TwistedEdwardsAffine};
+
+/// point over foreign field C::Range as range checked limbs of coordinates
+#[derive(Clone, Debug)]
+pub(crate) struct PointWires {
+    pub(crate) x: Vec<Wire>,
+    pub(crate) y: Vec<Wire>,
+}
+
+/// folding challenge truncated to k bits with its little endian bits
+#[derive(Clone, Debug)]
+pub(crate) struct ChallengeWires {
+    pub(crate) value: Wire,
+    pub(crate) bits: Vec<Wire>,
+}
+
+/// allocate limbs of foreign element range checked to L bits and top limb to remaining bits
+pub(crate) fn alloc_limbs<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    value: C::Range,
+) -> Vec<Wire> {
+    to_bits(value)
+        .chunks(limb_bits::<C::Scalar, C::Range>())
+        .map(|chunk| {
+            let bits = chunk
+                .iter()
+                .map(|bit| alloc_boolean(cs, *bit))
+                .collect::<Vec<_>>();
+            alloc(cs, pack_bits::<C>(&bits))
+        })
+        .collect()
+}
+
+/// allocate limbs constrained to constant foreign element
+pub(crate) fn constant_limbs<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    value: C::Range,
+) -> Vec<Wire> {
+    to_limbs::<C::Scalar, C::Range>(value)
+        .into_iter()
+        .map(|limb| alloc(cs, vec![limb.into()]))
+        .collect()
+}
+
+/// allocate limbs of integer below 2^k from its little endian bits
+pub(crate) fn bits_to_limbs<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    bits: &[Wire],
+) -> Vec<Wire> {
+    let mut limbs = bits
+        .chunks(limb_bits::<C::Scalar, C::Range>())
+        .map(|chunk| alloc(cs, pack_bits::<C>(chunk)))
+        .collect::<Vec<_>>();
+    while limbs.len() < limb_count::<C::Scalar, C::Range>() {
+        let zero = alloc(cs, vec![]);
+        limbs.push(zero)
+    }
+    limbs
+}
+
+/// allocate coordinates of point over C::Range as range checked limbs
+pub(crate) fn alloc_point<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    point: &C,
+) -> PointWires {
+    PointWires {
+        x: alloc_limbs(cs, point.get_x()),
+        y: alloc_limbs(cs, point.get_y()),
+    }
+}
+
+/// allocate coordinates constrained to constant point
+pub(crate) fn constant_point<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    point: &C,
+) -> PointWires {
+    PointWires {
+        x: constant_limbs(cs, point.get_x()),
+        y: constant_limbs(cs, point.get_y()),
+    }
+}
+
+/// allocate b ? p : q limb by limb for boolean b
+pub(crate) fn select_limbs<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    b: Wire,
+    p: &[Wire],
+    q: &[Wire],
+) -> Vec<Wire> {
+    p.iter()
+        .zip(q.iter())
+        .map(|(p, q)| select(cs, b, *p, *q))
+        .collect()
+}
+
+/// allocate b ? p : q for boolean b over limbs of coordinates
+pub(crate) fn select_point<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    b: Wire,
+    p: &PointWires,
+    q: &PointWires,
+) -> PointWires {
+    PointWires {
+        x: select_limbs(cs, b, &p.x, &q.x),
+        y: select_limbs(cs, b, &p.y, &q.y),
+    }
+}
+
+/// allocate lowest k bits of squeezed challenge
+/// decomposition into bits isn't checked to be below modulus so prover may choose between
+/// at most two truncations which at most halves challenge space
+pub(crate) fn alloc_challenge<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    challenge: Wire,
+) -> ChallengeWires {
+    let bits = to_bits(cs.value(challenge))
+        .into_iter()
+        .map(|bit| alloc_boolean(cs, bit))
+        .collect::<Vec<_>>();
+    // Σ 2^i · bi = challenge
+    cs.constrain(
+        pack_bits::<C>(&bits),
+        vec![C::Scalar::one().into()],
+        vec![challenge.into()],
+    );
+    let bits = bits[..challenge_bits::<C::Scalar, C::Range>()].to_vec();
+    let value = alloc(cs, pack_bits::<C>(&bits));
+    ChallengeWires { value, bits }
+}
+
+/// allocate y = a + r · b mod p for limbs of a, b over foreign field C::Range and r < 2^k
+/// q < 2^(k + 2) and carries cj are range checked so that a + r · b = y + q · p holds over
+/// integers by aj + r · bj - yj - q · pj + cj-1 = 2^L · cj for each limb with c-1 = cn-1 = 0
+pub(crate) fn mul_add<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    a: &[Wire],
+    r: Wire,
+    b: &[Wire],
+) -> Vec<Wire> {
+    let (zero, one) = (C::Scalar::zero(), C::Scalar::one());
+    let k = challenge_bits::<C::Scalar, C::Range>();
+    let shift = power_of_two::<C::Scalar>(limb_bits::<C::Scalar, C::Range>());
+    let offset = power_of_two::<C::Scalar>(k + 4);
+    let p = modulus_limbs::<C::Scalar, C::Range>();
+    let values = |cs: &ConstraintSystem<C>, wires: &[Wire]| {
+        wires.iter().map(|wire| cs.value(*wire)).collect::<Vec<_>>()
+    };
+    let (a_values, b_values, r_value) = (values(cs, a), values(cs, b), cs.value(r));
+
+    let value = from_limbs::<C::Scalar, C::Range>(&a_values)
+        + cast::<C::Scalar, C::Range>(r_value) * from_limbs::<C::Scalar, C::Range>(&b_values);
+    let y = alloc_limbs(cs, value);
+    let y_values = values(cs, &y);
+
+    // q = (a + r · b - y) / p is exact over C::Scalar since q is small
+    let sum = |limbs: &[C::Scalar]| {
+        limbs
+            .iter()
+            .rev()
+            .fold(zero, |sum, limb| sum * shift + *limb)
+    };
+    let q_value =
+        (sum(&a_values) + r_value * sum(&b_values) - sum(&y_values)) * sum(&p).invert().unwrap();
+    let q = alloc_ranged(cs, q_value, k + 2);
+
+    let inv_shift = shift.invert().unwrap();
+    let (mut carry, mut carry_value) = (vec![], zero);
+    for j in 0..p.len() {
+        let rb = mul(cs, r, b[j]);
+        let mut elements = vec![
+            a[j].into(),
+            rb.into(),
+            Element(y[j], zero - one),
+            Element(q, zero - p[j]),
+        ];
+        elements.extend(carry);
+        carry = vec![];
+        // top carry vanishes
+        if j + 1 < p.len() {
+            carry_value = (a_values[j] + r_value * b_values[j] - y_values[j] - q_value * p[j]
+                + carry_value)
+                * inv_shift;
+            // cj + 2^(k + 4) is range checked to k + 5 bits
+            let shifted = alloc_ranged(cs, carry_value + offset, k + 5);
+            elements.push(Element(shifted, zero - shift));
+            elements.push((shift * offset).into());
+            carry = vec![shifted.into(), (zero - offset).into()];
+        }
+        cs.constrain(elements, vec![one.into()], vec![]);
+    }
+    y
+}
+
+/// allocate y = a · b + c mod p for limbs of a, b and c over foreign field C::Range
+/// where c may be empty
+pub(crate) fn mul_limbs<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    a: &[Wire],
+    b: &[Wire],
+    c: &[Wire],
+) -> Vec<Wire> {
+    let value = |cs: &ConstraintSystem<C>, wires: &[Wire]| {
+        from_limbs::<C::Scalar, C::Range>(&wires.iter().map(|w| cs.value(*w)).collect::<Vec<_>>())
+    };
+    let y = value(cs, a) * value(cs, b) + value(cs, c);
+    let y = alloc_limbs(cs, y);
+    constrain_mul(cs, a, b, c, &y);
+    y
+}
+
+/// allocate y = a / b mod p for limbs of a and b ≠ 0 over foreign field C::Range
+pub(crate) fn div_limbs<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    a: &[Wire],
+    b: &[Wire],
+) -> Vec<Wire> {
+    let value = |cs: &ConstraintSystem<C>, wires: &[Wire]| {
+        from_limbs::<C::Scalar, C::Range>(&wires.iter().map(|w| cs.value(*w)).collect::<Vec<_>>())
+    };
+    let y = value(cs, a) * value(cs, b).invert().unwrap();
+    let y = alloc_limbs(cs, y);
+    // y · b = a mod p
+    constrain_mul(cs, &y, b, &[], a);
+    y
+}
+
+/// constrain a · b + c = q · p + y over integers for range checked limbs with q below 2^(|p| + 1)
+/// each column Σ ai · bj + cl - yl - Σ qi · pj + cl-1 = 2^L · cl holds with range checked
+/// carries below 2^(L + t + 1) in absolute value and no carry out of last column
+fn constrain_mul<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    a: &[Wire],
+    b: &[Wire],
+    c: &[Wire],
+    y: &[Wire],
+) {
+    let (zero, one) = (C::Scalar::zero(), C::Scalar::one());
+    let limb_bits = limb_bits::<C::Scalar, C::Range>();
+    let carry_bits = limb_bits + log2(limb_count::<C::Scalar, C::Range>()) + 1;
+    let shift = power_of_two::<C::Scalar>(limb_bits);
+    let offset = power_of_two::<C::Scalar>(carry_bits);
+    let p = modulus_limbs::<C::Scalar, C::Range>();
+    let values = |cs: &ConstraintSystem<C>, wires: &[Wire]| {
+        wires.iter().map(|wire| cs.value(*wire)).collect::<Vec<_>>()
+    };
+    let (a_values, b_values, c_values) = (values(cs, a), values(cs, b), values(cs, c));
+
+    let q = quotient::<C::Scalar, C::Range>(&a_values, &b_values, &c_values);
+    let q = q[..modulus_bits::<C::Range>() + 1]
+        .chunks(limb_bits)
+        .map(|chunk| {
+            let bits = chunk
+                .iter()
+                .map(|bit| alloc_boolean(cs, *bit))
+                .collect::<Vec<_>>();
+            alloc(cs, pack_bits::<C>(&bits))
+        })
+        .collect::<Vec<_>>();
+
+    // each column as Σ coeff · wire
+    let columns = (a.len() + b.len()).max(q.len() + p.len()) - 1;
+    let mut elements = vec![vec![]; columns];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            let ab = mul(cs, *a, *b);
+            elements[i + j].push(Element::from(ab));
+        }
+    }
+    for (i, q) in q.iter().enumerate() {
+        for (j, p) in p.iter().enumerate() {
+            elements[i + j].push(Element(*q, zero - *p));
+        }
+    }
+    c.iter()
+        .enumerate()
+        .for_each(|(l, c)| elements[l].push(Element::from(*c)));
+    y.iter()
+        .enumerate()
+        .for_each(|(l, y)| elements[l].push(Element(*y, zero - one)));
+
+    let inv_shift = shift.invert().unwrap();
+    let mut carry: Vec<Element<C::Scalar>> = vec![];
+    let len = elements.len();
+    for (l, mut elements) in elements.into_iter().enumerate() {
+        elements.extend(carry);
+        carry = vec![];
+        // top carry vanishes
+        if l + 1 < len {
+            let carry_value = evaluate(cs, &elements) * inv_shift;
+            // cl + 2^(L + t + 1) is range checked to L + t + 2 bits
+            let shifted = alloc_ranged(cs, carry_value + offset, carry_bits + 1);
+            elements.push(Element(shifted, zero - shift));
+            elements.push((shift * offset).into());
+            carry = vec![shifted.into(), (zero - offset).into()];
+        }
+        cs.constrain(elements, vec![one.into()], vec![]);
+    }
+}
+
+/// allocate p + q on twisted edwards curve C with coordinates over foreign field C::Range
+/// x3 = (x1y2 + y1x2) / (1 + d·x1x2y1y2), y3 = (y1y2 - a·x1x2) / (1 - d·x1x2y1y2)
+/// where denominators never vanish with complete addition law
+pub(crate) fn add_point<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    p: &PointWires,
+    q: &PointWires,
+) -> PointWires {
+    let zero = C::Range::zero();
+    let one = constant_limbs(cs, C::Range::one());
+    let minus_a = constant_limbs(cs, zero - C::PARAM_A);
+    let d = constant_limbs(cs, C::PARAM_D);
+    let minus_d = constant_limbs(cs, zero - C::PARAM_D);
+
+    let x1x2 = mul_limbs(cs, &p.x, &q.x, &[]);
+    let y1y2 = mul_limbs(cs, &p.y, &q.y, &[]);
+    let y1x2 = mul_limbs(cs, &p.y, &q.x, &[]);
+    let t = mul_limbs(cs, &x1x2, &y1y2, &[]);
+    let numerator_x = mul_limbs(cs, &p.x, &q.y, &y1x2);
+    let numerator_y = mul_limbs(cs, &x1x2, &minus_a, &y1y2);
+    let denominator_x = mul_limbs(cs, &t, &d, &one);
+    let denominator_y = mul_limbs(cs, &t, &minus_d, &one);
+    PointWires {
+        x: div_limbs(cs, &numerator_x, &denominator_x),
+        y: div_limbs(cs, &numerator_y, &denominator_y),
+    }
+}
+
+/// allocate k · p by double and add over little endian bits of k
+pub(crate) fn scalar_mul<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    bits: &[Wire],
+    p: &PointWires,
+) -> PointWires {
+    let identity = constant_point(cs, &C::ADDITIVE_IDENTITY);
+    let (acc, _) = bits
+        .iter()
+        .enumerate()
+        .fold((identity, p.clone()), |(acc, base), (i, bit)| {
+            let sum = add_point(cs, &acc, &base);
+            let acc = select_point(cs, *bit, &sum, &acc);
+            // last doubling is never used
+            let base = if i + 1 < bits.len() {
+                add_point(cs, &base, &base)
+            } else {
+                base
+            };
+            (acc, base)
+        });
+    acc
+}
+
+/// allocate value constrained to given number of little endian bits
+fn alloc_ranged<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    value: C::Scalar,
+    bits: usize,
+) -> Wire {
+    let bits = to_bits(value)[..bits]
+        .iter()
+        .map(|bit| alloc_boolean(cs, *bit))
+        .collect::<Vec<_>>();
+    alloc(cs, pack_bits::<C>(&bits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add_point, alloc_challenge, alloc_limbs, alloc_point, constrain_mul, div_limbs, mul_add,
+        mul_limbs, scalar_mul, PointWires,
+    };
+    use crate::constraint_system::ConstraintSystem;
+    use crate::nonnative::{cast, from_limbs, truncate};
+    use crate::wire::Wire;
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use rand_core::OsRng;
+    use zkstd::common::{CurveGroup, Group};
+
+    type Base = <Curve as CurveGroup>::Range;
+
+    #[test]
+    fn mul_add_test() {
+        let max = Base::zero() - Base::one();
+        let cases = (0..5)
+            .map(|_| {
+                (
+                    Scalar::random(OsRng),
+                    Base::random(OsRng),
+                    Base::random(OsRng),
+                )
+            })
+            .chain([(Scalar::zero() - Scalar::one(), max, max)]);
+        for (squeezed, a, b) in cases {
+            let mut cs = ConstraintSystem::<Curve>::new();
+            let squeezed = cs.private_wire(squeezed);
+            let r = alloc_challenge(&mut cs, squeezed);
+            let (a_limbs, b_limbs) = (alloc_limbs(&mut cs, a), alloc_limbs(&mut cs, b));
+            let y = mul_add(&mut cs, &a_limbs, r.value, &b_limbs);
+            let y = y.iter().map(|y| cs.value(*y)).collect::<Vec<_>>();
+            let r = truncate::<Scalar, Base>(cs.value(squeezed));
+
+            assert!(cs.is_sat());
+            assert_eq!(
+                from_limbs::<Scalar, Base>(&y),
+                a + cast::<Scalar, Base>(r) * b
+            )
+        }
+    }
+
+    #[test]
+    fn mul_limbs_test() {
+        let max = Base::zero() - Base::one();
+        let cases = (0..5)
+            .map(|_| {
+                (
+                    Base::random(OsRng),
+                    Base::random(OsRng),
+                    Base::random(OsRng),
+                )
+            })
+            .chain([(max, max, max), (Base::zero(), max, Base::one())]);
+        for (a, b, c) in cases {
+            let mut cs = ConstraintSystem::<Curve>::new();
+            let limbs = [a, b, c].map(|value| alloc_limbs(&mut cs, value));
+            let y = mul_limbs(&mut cs, &limbs[0], &limbs[1], &limbs[2]);
+            let product = mul_limbs(&mut cs, &limbs[0], &limbs[1], &[]);
+            let y = y.iter().map(|y| cs.value(*y)).collect::<Vec<_>>();
+            let product = product.iter().map(|y| cs.value(*y)).collect::<Vec<_>>();
+
+            assert!(cs.is_sat());
+            assert_eq!(from_limbs::<Scalar, Base>(&y), a * b + c);
+            assert_eq!(from_limbs::<Scalar, Base>(&product), a * b);
+        }
+
+        // quotient of a / b times b is a
+        let (a, b) = (Base::random(OsRng), Base::random(OsRng) + Base::one());
+        let mut cs = ConstraintSystem::<Curve>::new();
+        let (a_limbs, b_limbs) = (alloc_limbs(&mut cs, a), alloc_limbs(&mut cs, b));
+        let y = div_limbs(&mut cs, &a_limbs, &b_limbs);
+        let y = y.iter().map(|y| cs.value(*y)).collect::<Vec<_>>();
+
+        assert!(cs.is_sat());
+        assert_eq!(from_limbs::<Scalar, Base>(&y) * b, a);
+
+        // product off by one
+        let mut cs = ConstraintSystem::<Curve>::new();
+        let (a_limbs, b_limbs) = (alloc_limbs(&mut cs, a), alloc_limbs(&mut cs, b));
+        let y = alloc_limbs(&mut cs, a * b + Base::one());
+        constrain_mul(&mut cs, &a_limbs, &b_limbs, &[], &y);
+
+        assert!(!cs.is_sat())
+    }
+
+    #[test]
+    fn point_test() {
+        let (p, q): (Curve, Curve) = (Curve::random(OsRng).into(), Curve::random(OsRng).into());
+        let mut cs = ConstraintSystem::<Curve>::new();
+        let (p_wires, q_wires) = (alloc_point(&mut cs, &p), alloc_point(&mut cs, &q));
+        let sum = add_point(&mut cs, &p_wires, &q_wires);
+        let double = add_point(&mut cs, &p_wires, &p_wires);
+        let squeezed = cs.private_wire(Scalar::random(OsRng));
+        let r = alloc_challenge(&mut cs, squeezed);
+        let product = scalar_mul(&mut cs, &r.bits, &p_wires);
+        let r = truncate::<Scalar, Base>(cs.value(squeezed));
+        let point = |cs: &ConstraintSystem<Curve>, point: &PointWires| {
+            let limbs = |wires: &[Wire]| {
+                from_limbs::<Scalar, Base>(&wires.iter().map(|w| cs.value(*w)).collect::<Vec<_>>())
+            };
+            (limbs(&point.x), limbs(&point.y))
+        };
+        let expected = |point: Curve| (point.get_x(), point.get_y());
+
+        assert!(cs.is_sat());
+        assert_eq!(point(&cs, &sum), expected((p + q).into()));
+        assert_eq!(point(&cs, &double), expected(p.double().into()));
+        assert_eq!(point(&cs, &product), expected((p * r).into()))
+    }
+}
@@ -1,5 +1,8 @@
+use crate::nonnative::{from_bits, modulus_bits, to_bits, to_limbs};
+
 use blake2b_simd::{Params, State};
-use zkstd::common::FftField;
+use std::collections::VecDeque;
+use zkstd::common::{CurveGroup, FftField, PrimeField};
 
 const PERSONAL: &[u8; 16] = b"Nova_foldingHash";
 
@@ -22,3 +25,205 @@ impl Digest {
         F::from_hash(digest.as_array())
     }
 }
+
+/// poseidon state width with capacity 1 and rate 2
+pub(crate) const WIDTH: usize = 3;
+
+/// number of absorbed elements per permutation
+pub(crate) const RATE: usize = WIDTH - 1;
+
+/// number of full rounds split in half around partial rounds
+pub(crate) const FULL_ROUNDS: usize = 8;
+
+/// number of partial rounds for x^5 with width 3 over 255 bits field
+pub(crate) const PARTIAL_ROUNDS: usize = 57;
+
+/// poseidon round constants sampled by grain lfsr of reference implementation and cauchy mds
+/// matrix, so constants match published parameters for same field, width and rounds
+#[derive(Clone, Debug)]
+pub(crate) struct PoseidonConstants<F: PrimeField + FftField> {
+    pub(crate) round_constants: Vec<[F; WIDTH]>,
+    pub(crate) mds: [[F; WIDTH]; WIDTH],
+}
+
+impl<F: PrimeField + FftField> Default for PoseidonConstants<F> {
+    fn default() -> Self {
+        let mut grain = Grain::new::<F>();
+        let round_constants = (0..FULL_ROUNDS + PARTIAL_ROUNDS)
+            .map(|_| {
+                let mut constants = [F::zero(); WIDTH];
+                constants.iter_mut().for_each(|c| *c = grain.next_field());
+                constants
+            })
+            .collect();
+        // M[i][j] = 1 / (xi + yj) with xi = i and yj = WIDTH + j
+        let mut mds = [[F::zero(); WIDTH]; WIDTH];
+        mds.iter_mut().enumerate().for_each(|(i, row)| {
+            row.iter_mut().enumerate().for_each(|(j, m)| {
+                *m = F::from((i + WIDTH + j) as u64).invert().unwrap();
+            })
+        });
+        Self {
+            round_constants,
+            mds,
+        }
+    }
+}
+
+impl<F: PrimeField + FftField> PoseidonConstants<F> {
+    /// whether round applies s-box to every element of state
+    pub(crate) fn is_full_round(round: usize) -> bool {
+        !(FULL_ROUNDS / 2..FULL_ROUNDS / 2 + PARTIAL_ROUNDS).contains(&round)
+    }
+}
+
+/// grain lfsr b(i + 80) = b(i + 62) ⊕ b(i + 51) ⊕ b(i + 38) ⊕ b(i + 23) ⊕ b(i + 13) ⊕ b(i)
+/// seeded by poseidon parameters as in generate_parameters_grain.sage of reference
+struct Grain {
+    state: VecDeque<bool>,
+}
+
+impl Grain {
+    /// seed prime field, x^α s-box, field bits, width and rounds followed by 30 ones
+    /// and discard first 160 bits
+    fn new<F: FftField>() -> Self {
+        let mut state = VecDeque::with_capacity(80);
+        [
+            (1, 2),
+            (0, 4),
+            (modulus_bits::<F>(), 12),
+            (WIDTH, 12),
+            (FULL_ROUNDS, 10),
+            (PARTIAL_ROUNDS, 10),
+        ]
+        .iter()
+        .for_each(|(value, bits)| {
+            (0..*bits)
+                .rev()
+                .for_each(|i| state.push_back((value >> i) & 1 == 1))
+        });
+        state.extend([true; 30]);
+        let mut grain = Self { state };
+        (0..160).for_each(|_| {
+            grain.next_bit();
+        });
+        grain
+    }
+
+    fn next_bit(&mut self) -> bool {
+        let bit = [62, 51, 38, 23, 13, 0]
+            .iter()
+            .fold(false, |bit, i| bit ^ self.state[*i]);
+        self.state.pop_front();
+        self.state.push_back(bit);
+        bit
+    }
+
+    /// shrinking generator outputs second bit of pair only if first bit is one
+    fn next_shrunk_bit(&mut self) -> bool {
+        loop {
+            if self.next_bit() {
+                return self.next_bit();
+            }
+            self.next_bit();
+        }
+    }
+
+    /// big endian bits of field length resampled until they are below modulus
+    fn next_field<F: FftField>(&mut self) -> F {
+        let max = to_bits(F::zero() - F::one());
+        loop {
+            let mut bits = (0..max.len())
+                .map(|_| self.next_shrunk_bit())
+                .collect::<Vec<_>>();
+            bits.reverse();
+            if bits.iter().rev().le(max.iter().rev()) {
+                return from_bits(&bits);
+            }
+        }
+    }
+}
+
+/// poseidon x^5 sponge for io hashes and folding challenges which circuits recompute by
+/// `PoseidonGadget`, while challenges checked only natively use `ChallengeTranscript`
+/// capacity element is initialized with label for domain separation
+#[derive(Clone, Debug)]
+pub(crate) struct Poseidon<F: PrimeField + FftField> {
+    pub(crate) state: [F; WIDTH],
+    pub(crate) buffer: Vec<F>,
+    pub(crate) constants: PoseidonConstants<F>,
+}
+
+impl<F: PrimeField + FftField> Poseidon<F> {
+    pub(crate) fn new(label: &[u8]) -> Self {
+        let mut state = [F::zero(); WIDTH];
+        state[0] = domain(label);
+        Self {
+            state,
+            buffer: Vec::with_capacity(RATE),
+            constants: PoseidonConstants::default(),
+        }
+    }
+
+    pub(crate) fn absorb(&mut self, value: F) {
+        self.buffer.push(value);
+        if self.buffer.len() == RATE {
+            self.absorb_buffer()
+        }
+    }
+
+    /// absorb limbs of affine coordinates of point over foreign field
+    pub(crate) fn absorb_point<C: CurveGroup<Scalar = F>>(&mut self, point: &C) {
+        to_limbs::<F, C::Range>(point.get_x())
+            .into_iter()
+            .chain(to_limbs::<F, C::Range>(point.get_y()))
+            .for_each(|limb| self.absorb(limb))
+    }
+
+    /// pad buffer with 1 and zeros and output first rate element
+    pub(crate) fn squeeze(&mut self) -> F {
+        self.buffer.push(F::one());
+        self.buffer.resize(RATE, F::zero());
+        self.absorb_buffer();
+        self.state[1]
+    }
+
+    fn absorb_buffer(&mut self) {
+        self.buffer
+            .drain(..)
+            .zip(self.state.iter_mut().skip(1))
+            .for_each(|(value, state)| *state += value);
+        self.permute()
+    }
+
+    fn permute(&mut self) {
+        let PoseidonConstants {
+            round_constants,
+            mds,
+        } = &self.constants;
+        round_constants.iter().enumerate().for_each(|(round, c)| {
+            let full = PoseidonConstants::<F>::is_full_round(round);
+            let mut state = self.state;
+            state.iter_mut().enumerate().for_each(|(i, s)| {
+                *s += c[i];
+                if full || i == 0 {
+                    *s = s.square().square() * *s
+                }
+            });
+            self.state.iter_mut().zip(mds.iter()).for_each(|(s, row)| {
+                *s = row
+                    .iter()
+                    .zip(state.iter())
+                    .fold(F::zero(), |sum, (m, x)| sum + *m * x)
+            });
+        })
+    }
+}
+
+/// capacity element binding sponge label
+pub(crate) fn domain<F: FftField>(label: &[u8]) -> F {
+    let mut hasher = Digest::default();
+    hasher.update(b"sponge");
+    hasher.update(label);
+    hasher.finalize()
+}
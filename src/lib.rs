@@ -1,18 +1,72 @@
+//! Nova folding scheme and IVC over twisted edwards curves
+//!
+//! - setup: `Nifs::g` or `PedersenCommitment::from_r1cs` samples public parameters and
+//!   `PedersenCommitment::from_r1cs_label` derives them transparently by hash to curve
+//! - circuits: `ConstraintSystem::setup` records shape and `ConstraintSystem::finalize` outputs
+//!   structure, instance and witness for folding
+//! - debugging: `ConstraintSystem::unsatisfied` reports failing constraints with their namespace and label
+//!   and `ConstraintSystem::stats` counts constraints per namespace
+//! - key generation: `Nifs::k` outputs prover and verification key for r1cs structure
+//! - folding: `Prover::prove` folds r1cs into relaxed r1cs and `Nifs::verify` folds instances
+//! - proving: `Prover::recurse` proves each step of `StepCircuit` and `Prover::compress` compresses the final proof
+//! - verification: `Verifier::verify` and `Verifier::verify_compressed` check ivc proofs
+//! - pcd: `PcdProver::prove` proves subranges on threads and merges them in binary tree
+//! - ccs: `CcsStructure` generalizes r1cs and `Nimfs` multi-folds lcccs with cccs by sumcheck
+//! - high degree gates: `Gate` builds ccs rows like x^5 and `Protostar` folds them with d - 1 cross terms
+//! - non-uniform ivc: `NivcProver::recurse` runs step circuit selected by program counter zi[0]
+//!
+//! commitments of running instances are folded in augmented, merge and nivc circuits by
+//! non-native twisted edwards arithmetic so every exported prover binds folded commitments
 #![allow(dead_code)]
 
+mod augmented_circuit;
+mod ccs;
 mod constraint_system;
+mod error;
+mod gadget;
 mod hash;
+mod linear_combination;
 mod matrix;
+mod merge_circuit;
 mod nifs;
+mod nimfs;
+mod nivc_circuit;
+mod nonnative;
 mod proof;
+mod protostar;
 mod prover;
 mod public_param;
 mod r1cs;
 mod relaxed_r1cs;
+mod snark;
+mod step_circuit;
 mod transcript;
+mod verifier;
 mod wire;
 
 #[cfg(test)]
 mod tests;
 
-pub use constraint_system::ConstraintSystem;
+pub use ccs::{
+    Cccs, CcsStructure, Gate, Instance as LcccsInstance, Lcccs, RelaxedCcs, Witness as LcccsWitness,
+};
+pub use constraint_system::{CircuitStats, ConstraintSystem, Namespace};
+pub use error::Error;
+pub use linear_combination::LinearCombination;
+pub use nifs::{Nifs, VerificationKey};
+pub use nimfs::Nimfs;
+pub use proof::{CompressedProof, IvcProof, MultifoldingProof, NivcProof, PcdProof};
+pub use protostar::Protostar;
+pub use prover::{NivcProver, PcdProver, Prover};
+pub use public_param::PedersenCommitment;
+pub use r1cs::{
+    Instance as R1csInstance, R1csInstance as R1cs, R1csStructure, UnsatisfiedConstraint,
+    Witness as R1csWitness,
+};
+pub use relaxed_r1cs::{
+    Instance as RelaxedR1csInstance, RelaxedR1csInstance as RelaxedR1cs,
+    Witness as RelaxedR1csWitness,
+};
+pub use step_circuit::StepCircuit;
+pub use verifier::{NivcVerifier, PcdVerifier, Verifier};
+pub use wire::Wire;
@@ -0,0 +1,136 @@
+use crate::matrix::Element;
+use crate::wire::Wire;
+
+use zkstd::common::{Add, Mul, Neg, PrimeField, Sub};
+
+/// linear combination Σ coeff · wire where constants are coefficients of `Wire::One`
+#[derive(Clone, Debug, Default)]
+pub struct LinearCombination<F: PrimeField>(pub(crate) Vec<Element<F>>);
+
+impl<F: PrimeField> LinearCombination<F> {
+    /// empty linear combination evaluating to zero
+    pub fn zero() -> Self {
+        Self(vec![])
+    }
+
+    /// constant c · one
+    pub fn constant(c: F) -> Self {
+        Self(vec![Element(Wire::One, c)])
+    }
+
+    /// single term coeff · wire
+    pub fn term(wire: Wire, coeff: F) -> Self {
+        Self(vec![Element(wire, coeff)])
+    }
+
+    /// terms as (wire, coeff)
+    pub fn terms(&self) -> Vec<(Wire, F)> {
+        self.0.iter().map(Element::get).collect()
+    }
+}
+
+impl<F: PrimeField> From<Wire> for LinearCombination<F> {
+    fn from(wire: Wire) -> Self {
+        Self::term(wire, F::one())
+    }
+}
+
+impl<F: PrimeField> From<F> for LinearCombination<F> {
+    fn from(c: F) -> Self {
+        Self::constant(c)
+    }
+}
+
+impl<F: PrimeField> Add for LinearCombination<F> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self.0.extend(rhs.0);
+        self
+    }
+}
+
+impl<F: PrimeField> Add<Wire> for LinearCombination<F> {
+    type Output = Self;
+
+    fn add(self, rhs: Wire) -> Self {
+        self + Self::from(rhs)
+    }
+}
+
+impl<F: PrimeField> Sub for LinearCombination<F> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl<F: PrimeField> Sub<Wire> for LinearCombination<F> {
+    type Output = Self;
+
+    fn sub(self, rhs: Wire) -> Self {
+        self - Self::from(rhs)
+    }
+}
+
+impl<F: PrimeField> Neg for LinearCombination<F> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(
+            self.0
+                .into_iter()
+                .map(|Element(wire, coeff)| Element(wire, -coeff))
+                .collect(),
+        )
+    }
+}
+
+impl<F: PrimeField> Mul<F> for LinearCombination<F> {
+    type Output = Self;
+
+    fn mul(self, rhs: F) -> Self {
+        Self(
+            self.0
+                .into_iter()
+                .map(|Element(wire, coeff)| Element(wire, coeff * rhs))
+                .collect(),
+        )
+    }
+}
+
+impl<F: PrimeField> Mul<F> for Wire {
+    type Output = LinearCombination<F>;
+
+    fn mul(self, rhs: F) -> LinearCombination<F> {
+        LinearCombination::term(self, rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinearCombination;
+    use crate::wire::Wire;
+
+    use jub_jub::Fr as Scalar;
+
+    #[test]
+    fn linear_combination_test() {
+        let (x, y) = (Wire::witness(0), Wire::instance(0));
+        let (two, three, seven) = (Scalar::from(2), Scalar::from(3), Scalar::from(7));
+
+        // 3x - 2y + 7
+        let lc = x * three - y * two + LinearCombination::constant(seven);
+        assert_eq!(lc.terms(), vec![(x, three), (y, -two), (Wire::One, seven)]);
+        assert_eq!(
+            (-lc * two).terms(),
+            vec![(x, -three * two), (y, two * two), (Wire::One, -seven * two)]
+        );
+        assert_eq!(
+            (LinearCombination::from(x) + y - x).terms(),
+            vec![(x, Scalar::one()), (y, Scalar::one()), (x, -Scalar::one())]
+        );
+        assert!(LinearCombination::<Scalar>::zero().terms().is_empty())
+    }
+}
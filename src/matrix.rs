@@ -10,9 +10,11 @@ use zkstd::common::PrimeField;
 pub(crate) struct SparseMatrix<F: PrimeField>(pub(crate) Vec<Vec<Element<F>>>);
 
 impl<F: PrimeField> SparseMatrix<F> {
+    /// matrix and z = (W, x, u) product where u is assigned to constant wire
     pub(crate) fn prod(
         &self,
         m: usize,
+        u: F,
         x: &DenseVectors<F>,
         w: &DenseVectors<F>,
     ) -> DenseVectors<F> {
@@ -23,7 +25,7 @@ impl<F: PrimeField> SparseMatrix<F> {
                 let value = match wire {
                     Wire::Instance(i) => x[i],
                     Wire::Witness(i) => w[i],
-                    Wire::One => F::one(),
+                    Wire::One => u,
                 };
                 sum + coeff * value
             })
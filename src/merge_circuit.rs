@@ -0,0 +1,394 @@
+use crate::augmented_circuit::{
+    absorb_relaxed_instance, alloc_instance, alloc_relaxed_instance, fold_commitments,
+    fold_instance, trivial_instance, CommitmentFold, RelaxedInstanceWires,
+};
+use crate::constraint_system::ConstraintSystem;
+use crate::gadget::{alloc, alloc_challenge, alloc_point, is_zero, mul, select, PoseidonGadget};
+use crate::matrix::{DenseVectors, Element};
+use crate::nifs::{ivc_challenge, pcd_challenge, VerificationKey};
+use crate::r1cs::{Instance as R1csInstance, R1csStructure};
+use crate::relaxed_r1cs::Instance as RelaxedR1csInstance;
+use crate::step_circuit::StepCircuit;
+use crate::wire::Wire;
+
+use zkstd::common::{Group, Ring, TwistedEdwardsAffine};
+
+/// public io length of M which is H(vk, a, b, za, zb, U)
+pub(crate) const MERGE_IO: usize = 1;
+
+/// child of pcd node proving za → zb over range [a, b) with (U, u)
+/// child over empty range is base case and isn't folded
+#[derive(Clone, Debug)]
+pub(crate) struct MergeChild<C: TwistedEdwardsAffine> {
+    pub(crate) range: (usize, usize),
+    pub(crate) z_in: Vec<C::Scalar>,
+    pub(crate) z_out: Vec<C::Scalar>,
+    /// running instance U
+    pub(crate) relaxed_instance: RelaxedR1csInstance<C>,
+    /// node instance u
+    pub(crate) instance: R1csInstance<C>,
+    /// commitment for cross term T folding u into U
+    pub(crate) commit_t: C,
+}
+
+impl<C: TwistedEdwardsAffine> MergeChild<C> {
+    /// base case over empty range [a, a) with trivial instances
+    pub(crate) fn base(a: usize, z: Vec<C::Scalar>) -> Self {
+        Self {
+            range: (a, a),
+            z_in: z.clone(),
+            z_out: z,
+            relaxed_instance: trivial_instance(MERGE_IO),
+            instance: R1csInstance {
+                commit_w: C::ADDITIVE_IDENTITY,
+                x: DenseVectors(vec![C::Scalar::zero(); MERGE_IO]),
+            },
+            commit_t: C::ADDITIVE_IDENTITY,
+        }
+    }
+
+    pub(crate) fn is_base(&self) -> bool {
+        self.range.0 == self.range.1
+    }
+
+    /// running instance U' folded from (U, u, T) outside of circuit
+    pub(crate) fn folded_instance(
+        &self,
+        vk: &VerificationKey<C::Scalar>,
+    ) -> RelaxedR1csInstance<C> {
+        if self.is_base() {
+            trivial_instance(MERGE_IO)
+        } else {
+            let r = ivc_challenge(vk, &self.relaxed_instance, &self.instance, &self.commit_t);
+            self.relaxed_instance
+                .fold(self.instance.clone(), r, self.commit_t)
+        }
+    }
+}
+
+/// merge circuit M for pcd node over children [a, m) and [m, b)
+/// checks io hashes of children, folds (U, u, T) of each child, folds both running instances
+/// into U' and outputs H(vk, a, b', za, zb', U') where (b', zb') is (b + 1, F(zb)) if step is
+/// applied otherwise (b, zb), so leaves apply F to base children and inner nodes only merge
+#[derive(Clone, Debug)]
+pub(crate) struct MergeCircuit<C: TwistedEdwardsAffine> {
+    /// verification key digest
+    pub(crate) digest: C::Scalar,
+    pub(crate) left: MergeChild<C>,
+    pub(crate) right: MergeChild<C>,
+    /// commitment for cross term T folding running instances of children
+    pub(crate) commit_t: C,
+    /// whether F is applied after merge
+    pub(crate) step: bool,
+}
+
+/// child wires with folded running instance
+struct ChildWires {
+    range: (Wire, Wire),
+    is_base: Wire,
+    z_in: Vec<Wire>,
+    z_out: Vec<Wire>,
+    folded_instance: RelaxedInstanceWires,
+}
+
+impl<C: TwistedEdwardsAffine> MergeCircuit<C> {
+    /// leaf applying F to z over [a, a + 1)
+    pub(crate) fn leaf(digest: C::Scalar, a: usize, z: Vec<C::Scalar>) -> Self {
+        Self {
+            digest,
+            left: MergeChild::base(a, z.clone()),
+            right: MergeChild::base(a, z),
+            commit_t: C::ADDITIVE_IDENTITY,
+            step: true,
+        }
+    }
+
+    /// range [a, b') proven by node
+    pub(crate) fn range(&self) -> (usize, usize) {
+        (self.left.range.0, self.right.range.1 + self.step as usize)
+    }
+
+    /// running instance U' folded from both children outside of circuit
+    pub(crate) fn folded_instance(&self) -> RelaxedR1csInstance<C> {
+        if self.left.is_base() && self.right.is_base() {
+            trivial_instance(MERGE_IO)
+        } else {
+            let vk = VerificationKey {
+                digest: self.digest,
+            };
+            let left = self.left.folded_instance(&vk);
+            let right = self.right.folded_instance(&vk);
+            let r = pcd_challenge(&vk, &right, &left, &self.commit_t);
+            right.fold_relaxed(left, r, self.commit_t)
+        }
+    }
+
+    /// synthesize M with step circuit F and output zb'
+    pub(crate) fn synthesize(
+        &self,
+        cs: &mut ConstraintSystem<C>,
+        step: &impl StepCircuit<C>,
+    ) -> Vec<C::Scalar> {
+        let (zero, one) = (C::Scalar::zero(), C::Scalar::one());
+        let digest = cs.private_wire(self.digest);
+        let left = synthesize_child(cs, digest, &self.left);
+        let right = synthesize_child(cs, digest, &self.right);
+        let commit_t = alloc_point(cs, &self.commit_t);
+        let apply = cs.private_wire(if self.step { one } else { zero });
+        // apply · apply = apply
+        cs.mul_constraint(apply, apply, apply);
+
+        // children are adjacent with matching z
+        cs.equal_constraint(left.range.1, right.range.0);
+        left.z_out
+            .iter()
+            .zip(right.z_in.iter())
+            .for_each(|(left, right)| cs.equal_constraint(*left, *right));
+
+        // r ← p(vk, U2, U1, T)
+        let mut transcript = PoseidonGadget::new(b"nifs_relaxed");
+        transcript.absorb(cs, digest);
+        absorb_relaxed_instance(cs, &mut transcript, &right.folded_instance);
+        absorb_relaxed_instance(cs, &mut transcript, &left.folded_instance);
+        transcript.absorb_point(cs, &commit_t);
+        let squeezed = transcript.squeeze(cs);
+        let r = alloc_challenge(cs, squeezed);
+
+        // U'.u = U1.u + r · U2.u and U'.x = U1.x + r · U2.x
+        let ru = mul(cs, r.value, right.folded_instance.u);
+        let u = alloc(cs, vec![left.folded_instance.u.into(), ru.into()]);
+        let x = left
+            .folded_instance
+            .x
+            .iter()
+            .zip(right.folded_instance.x.iter())
+            .map(|(left, right)| {
+                let rx = mul(cs, r.value, *right);
+                alloc(cs, vec![(*left).into(), rx.into()])
+            })
+            .collect();
+
+        // W' = U1.W + r · U2.W and E' = U1.E + r · (T + r · U2.E) which is trivial if both
+        // children are base case
+        let is_base = mul(cs, left.is_base, right.is_base);
+        let (commit_w, commit_e) = fold_commitments(
+            cs,
+            CommitmentFold {
+                is_base,
+                r: &r,
+                commit_w: (
+                    &left.folded_instance.commit_w,
+                    &right.folded_instance.commit_w,
+                ),
+                commit_e: (
+                    &left.folded_instance.commit_e,
+                    &right.folded_instance.commit_e,
+                ),
+                commit_t: &commit_t,
+            },
+        );
+        let folded_instance = RelaxedInstanceWires {
+            commit_w,
+            commit_e,
+            u,
+            x,
+        };
+
+        // zb' = F(zb) and b' = b + 1 if step is applied
+        let z_step = step.synthesize(cs, &right.z_out);
+        let z_out = z_step
+            .iter()
+            .zip(right.z_out.iter())
+            .map(|(z_step, z)| select(cs, apply, *z_step, *z))
+            .collect::<Vec<_>>();
+        let end = alloc(cs, vec![right.range.1.into(), apply.into()]);
+
+        // output H(vk, a, b', za, zb', U')
+        let hash = hash_node(
+            cs,
+            digest,
+            (left.range.0, end),
+            &left.z_in,
+            &z_out,
+            &folded_instance,
+        );
+        let value = cs.value(hash);
+        let output = cs.public_wire(value);
+        cs.equal_constraint(hash, output);
+
+        z_out.iter().map(|z| cs.value(*z)).collect()
+    }
+}
+
+/// r1cs structure of M for step circuit F
+pub(crate) fn merge_r1cs<C: TwistedEdwardsAffine>(step: &impl StepCircuit<C>) -> R1csStructure<C> {
+    let mut cs = ConstraintSystem::new();
+    let z = vec![C::Scalar::zero(); step.arity()];
+    let circuit = MergeCircuit::leaf(C::Scalar::zero(), 0, z);
+    circuit.synthesize(&mut cs, step);
+    cs.structure()
+}
+
+/// check io hash of child and fold its (U, u, T) after base case
+fn synthesize_child<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    digest: Wire,
+    child: &MergeChild<C>,
+) -> ChildWires {
+    let (zero, one) = (C::Scalar::zero(), C::Scalar::one());
+    let a = cs.private_wire(C::Scalar::from(child.range.0 as u64));
+    let b = cs.private_wire(C::Scalar::from(child.range.1 as u64));
+    let z_in = child
+        .z_in
+        .iter()
+        .map(|z| cs.private_wire(*z))
+        .collect::<Vec<_>>();
+    let z_out = child
+        .z_out
+        .iter()
+        .map(|z| cs.private_wire(*z))
+        .collect::<Vec<_>>();
+    let relaxed_instance = alloc_relaxed_instance(cs, &child.relaxed_instance);
+    let instance = alloc_instance(cs, &child.instance);
+    let commit_t = alloc_point(cs, &child.commit_t);
+
+    // empty range [a, a) is base case
+    let len = alloc(cs, vec![b.into(), Element(a, zero - one)]);
+    let is_base = is_zero(cs, len);
+    let not_base = vec![one.into(), Element(is_base, zero - one)];
+
+    // za = zb at base case
+    z_in.iter().zip(z_out.iter()).for_each(|(z_in, z_out)| {
+        cs.constrain(
+            vec![is_base.into()],
+            vec![(*z_out).into(), Element(*z_in, zero - one)],
+            vec![],
+        )
+    });
+
+    // u.x = H(vk, a, b, za, zb, U) after base case
+    let hash = hash_node(cs, digest, (a, b), &z_in, &z_out, &relaxed_instance);
+    cs.constrain(
+        not_base,
+        vec![instance.x[0].into(), Element(hash, zero - one)],
+        vec![],
+    );
+
+    // fold (U, u, T) into U' after base case
+    let folded_instance = fold_instance(
+        cs,
+        (digest, is_base),
+        (&relaxed_instance, &instance),
+        &commit_t,
+    );
+
+    ChildWires {
+        range: (a, b),
+        is_base,
+        z_in,
+        z_out,
+        folded_instance,
+    }
+}
+
+/// H(vk, a, b, za, zb, U) corresponding to `RelaxedR1csInstance::pcd_hash`
+fn hash_node<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    digest: Wire,
+    range: (Wire, Wire),
+    z_in: &[Wire],
+    z_out: &[Wire],
+    instance: &RelaxedInstanceWires,
+) -> Wire {
+    let mut hasher = PoseidonGadget::new(b"pcd");
+    hasher.absorb(cs, digest);
+    hasher.absorb(cs, range.0);
+    hasher.absorb(cs, range.1);
+    z_in.iter()
+        .chain(z_out.iter())
+        .for_each(|z| hasher.absorb(cs, *z));
+    absorb_relaxed_instance(cs, &mut hasher, instance);
+    hasher.squeeze(cs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_r1cs, MergeChild, MergeCircuit};
+    use crate::constraint_system::ConstraintSystem;
+    use crate::matrix::DenseVectors;
+    use crate::r1cs::Instance as R1csInstance;
+    use crate::tests::ExampleCircuit;
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use zkstd::common::CurveGroup;
+
+    #[test]
+    fn merge_circuit_test() {
+        let digest = Scalar::from(11);
+        let z0 = vec![Scalar::from(2)];
+        let r1cs = merge_r1cs::<Curve>(&ExampleCircuit);
+
+        // leaves over [0, 1) and [1, 2)
+        let leaf = |a, z: Vec<Scalar>| {
+            let circuit = MergeCircuit::<Curve>::leaf(digest, a, z.clone());
+            let mut cs = ConstraintSystem::new();
+            let z_out = circuit.synthesize(&mut cs, &ExampleCircuit);
+            let structure = cs.structure();
+            let u = circuit.folded_instance();
+
+            assert!(cs.is_sat());
+            assert_eq!((structure.m, structure.l), (r1cs.m, r1cs.l));
+            assert_eq!(
+                cs.assignment()[0],
+                u.pcd_hash(digest, (a, a + 1), &z, &z_out)
+            );
+            MergeChild {
+                range: (a, a + 1),
+                z_in: z,
+                z_out,
+                relaxed_instance: u,
+                instance: R1csInstance {
+                    commit_w: Curve::ADDITIVE_GENERATOR,
+                    x: DenseVectors(vec![cs.assignment()[0]]),
+                },
+                commit_t: Curve::ADDITIVE_GENERATOR,
+            }
+        };
+        let left = leaf(0, z0.clone());
+        let right = leaf(1, left.z_out.clone());
+
+        // merge [0, 1) and [1, 2) into [0, 2)
+        let circuit = MergeCircuit {
+            digest,
+            left,
+            right,
+            commit_t: Curve::ADDITIVE_GENERATOR,
+            step: false,
+        };
+        let mut cs = ConstraintSystem::new();
+        let z2 = circuit.synthesize(&mut cs, &ExampleCircuit);
+        let u = circuit.folded_instance();
+
+        assert!(cs.is_sat());
+        assert_eq!(cs.structure().m, r1cs.m);
+        assert_eq!(circuit.range(), (0, 2));
+        assert_eq!(z2, circuit.right.z_out);
+        assert_eq!(cs.assignment()[0], u.pcd_hash(digest, (0, 2), &z0, &z2));
+
+        // invalid io hash of child instance
+        let mut invalid_circuit = circuit.clone();
+        invalid_circuit.left.instance.x = DenseVectors(vec![Scalar::one()]);
+        let mut cs = ConstraintSystem::new();
+        invalid_circuit.synthesize(&mut cs, &ExampleCircuit);
+
+        assert!(!cs.is_sat());
+
+        // non adjacent children
+        let mut invalid_circuit = circuit;
+        invalid_circuit.right.z_in = z0;
+        let mut cs = ConstraintSystem::new();
+        invalid_circuit.synthesize(&mut cs, &ExampleCircuit);
+
+        assert!(!cs.is_sat())
+    }
+}
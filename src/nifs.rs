@@ -1,27 +1,306 @@
+use crate::error::Error;
+use crate::hash::{Digest, Poseidon};
+use crate::nonnative::truncate;
 use crate::prover::Prover;
 use crate::public_param::PedersenCommitment;
-use crate::r1cs::R1csStructure;
+use crate::r1cs::{Instance as R1csInstance, R1csStructure};
+use crate::relaxed_r1cs::Instance as RelaxedR1csInstance;
+use crate::transcript::{ChallengeTranscript, Transcript};
 
-use zkstd::common::{PrimeField, RngCore, TwistedEdwardsAffine};
+use zkstd::common::{FftField, PrimeField, RngCore, TwistedEdwardsAffine};
 
-struct Nifs<C: TwistedEdwardsAffine> {
+/// non-interactive folding scheme (G, K, P, V) where P is `Prover::prove`
+pub struct Nifs<C: TwistedEdwardsAffine> {
     pp: PedersenCommitment<C>,
 }
 
 impl<C: TwistedEdwardsAffine> Nifs<C> {
-    pub(crate) fn g(λ: u64, r: impl RngCore) -> PedersenCommitment<C> {
-        PedersenCommitment::new(λ, r)
+    /// G(1^λ) → pp with 2^λ + 1 bases
+    pub fn g(λ: u64, r: impl RngCore) -> PedersenCommitment<C> {
+        PedersenCommitment::new((1 << λ) + 1, r)
     }
 
-    pub(crate) fn k(
+    /// K(pp, s) → (pk, vk)
+    pub fn k(
         pp: PedersenCommitment<C>,
         r1cs: R1csStructure<C>,
     ) -> (Prover<C>, VerificationKey<C::Scalar>) {
-        let digest = pp.digest();
-        (Prover { pp, f: r1cs, i: 0 }, VerificationKey { digest })
+        let prover = Prover::new(pp, r1cs);
+        let vk = prover.vk.clone();
+        (prover, vk)
+    }
+
+    /// V(vk, U, u, T) → U' folding committed instances without witnesses
+    pub fn verify(
+        vk: &VerificationKey<C::Scalar>,
+        relaxed_instance: RelaxedR1csInstance<C>,
+        instance: R1csInstance<C>,
+        commit_t: C,
+    ) -> RelaxedR1csInstance<C> {
+        let r = challenge(vk, &relaxed_instance, &instance, &commit_t);
+        relaxed_instance.fold(instance, r, commit_t)
+    }
+
+    /// V(vk, U, (u1, ..., uk), (T1, ..., T2k-1)) → U' folding k committed instances at once
+    /// with single challenge r as U' = U + Σ r^i · ui
+    pub fn verify_batch(
+        vk: &VerificationKey<C::Scalar>,
+        relaxed_instance: RelaxedR1csInstance<C>,
+        instances: Vec<R1csInstance<C>>,
+        commit_t: Vec<C>,
+    ) -> Result<RelaxedR1csInstance<C>, Error> {
+        if commit_t.len() != (2 * instances.len()).saturating_sub(1) {
+            return Err(Error::InvalidCrossTerms);
+        }
+        let r = batch_challenge(vk, &relaxed_instance, &instances, &commit_t);
+        Ok(relaxed_instance.fold_batch(instances, r, &commit_t))
+    }
+
+    /// V(vk, U2, U1, T) → U' folding two committed relaxed instances
+    pub fn verify_relaxed(
+        vk: &VerificationKey<C::Scalar>,
+        relaxed_instance: RelaxedR1csInstance<C>,
+        instance: RelaxedR1csInstance<C>,
+        commit_t: C,
+    ) -> RelaxedR1csInstance<C> {
+        let r = relaxed_challenge(vk, &relaxed_instance, &instance, &commit_t);
+        relaxed_instance.fold_relaxed(instance, r, commit_t)
+    }
+}
+
+/// folding challenge r ← p(vk, U, u, T) shared by prover and verifier of standalone nifs
+pub(crate) fn challenge<C: TwistedEdwardsAffine>(
+    vk: &VerificationKey<C::Scalar>,
+    relaxed_instance: &RelaxedR1csInstance<C>,
+    instance: &R1csInstance<C>,
+    commit_t: &C,
+) -> C::Scalar {
+    let mut transcript = <Transcript as ChallengeTranscript<C>>::init(b"nifs");
+    <Transcript as ChallengeTranscript<C>>::append_scalar(&mut transcript, b"vk", &vk.digest);
+    relaxed_instance.absorb(&mut transcript);
+    instance.absorb(&mut transcript);
+    transcript.append_point(b"commit_t", commit_t);
+    <Transcript as ChallengeTranscript<C>>::challenge_scalar(&mut transcript, b"r")
+}
+
+/// folding challenge r ← p(vk, U2, U1, T) for two relaxed instances of standalone nifs
+pub(crate) fn relaxed_challenge<C: TwistedEdwardsAffine>(
+    vk: &VerificationKey<C::Scalar>,
+    relaxed_instance: &RelaxedR1csInstance<C>,
+    instance: &RelaxedR1csInstance<C>,
+    commit_t: &C,
+) -> C::Scalar {
+    let mut transcript = <Transcript as ChallengeTranscript<C>>::init(b"nifs_relaxed");
+    <Transcript as ChallengeTranscript<C>>::append_scalar(&mut transcript, b"vk", &vk.digest);
+    relaxed_instance.absorb(&mut transcript);
+    instance.absorb(&mut transcript);
+    transcript.append_point(b"commit_t", commit_t);
+    <Transcript as ChallengeTranscript<C>>::challenge_scalar(&mut transcript, b"r")
+}
+
+/// folding challenge r ← p(vk, U, u, T) of ivc, pcd and nivc steps
+/// augmented, merge and nivc circuits recompute it with `PoseidonGadget`, so it's squeezed by
+/// poseidon and truncated to k bits so that r is same integer over scalar and base fields
+pub(crate) fn ivc_challenge<C: TwistedEdwardsAffine>(
+    vk: &VerificationKey<C::Scalar>,
+    relaxed_instance: &RelaxedR1csInstance<C>,
+    instance: &R1csInstance<C>,
+    commit_t: &C,
+) -> C::Scalar {
+    let mut sponge = Poseidon::new(b"nifs");
+    sponge.absorb(vk.digest);
+    relaxed_instance.absorb_sponge(&mut sponge);
+    instance.absorb_sponge(&mut sponge);
+    sponge.absorb_point(commit_t);
+    truncate::<C::Scalar, C::Range>(sponge.squeeze())
+}
+
+/// folding challenge r ← p(vk, U2, U1, T) of pcd merges recomputed in merge circuit by
+/// `PoseidonGadget` and truncated to k bits like `ivc_challenge`
+pub(crate) fn pcd_challenge<C: TwistedEdwardsAffine>(
+    vk: &VerificationKey<C::Scalar>,
+    relaxed_instance: &RelaxedR1csInstance<C>,
+    instance: &RelaxedR1csInstance<C>,
+    commit_t: &C,
+) -> C::Scalar {
+    let mut sponge = Poseidon::new(b"nifs_relaxed");
+    sponge.absorb(vk.digest);
+    relaxed_instance.absorb_sponge(&mut sponge);
+    instance.absorb_sponge(&mut sponge);
+    sponge.absorb_point(commit_t);
+    truncate::<C::Scalar, C::Range>(sponge.squeeze())
+}
+
+/// folding challenge r ← p(vk, U, (u1, ..., uk), (T1, ..., T2k-1)) for batch of instances
+/// which is never recomputed in circuit so it's squeezed by transcript without truncation
+pub(crate) fn batch_challenge<C: TwistedEdwardsAffine>(
+    vk: &VerificationKey<C::Scalar>,
+    relaxed_instance: &RelaxedR1csInstance<C>,
+    instances: &[R1csInstance<C>],
+    commit_t: &[C],
+) -> C::Scalar {
+    let mut transcript = <Transcript as ChallengeTranscript<C>>::init(b"nifs_batch");
+    <Transcript as ChallengeTranscript<C>>::append_scalar(&mut transcript, b"vk", &vk.digest);
+    relaxed_instance.absorb(&mut transcript);
+    instances
+        .iter()
+        .for_each(|instance| instance.absorb(&mut transcript));
+    commit_t
+        .iter()
+        .for_each(|commit_t| transcript.append_point(b"commit_t", commit_t));
+    <Transcript as ChallengeTranscript<C>>::challenge_scalar(&mut transcript, b"r")
+}
+
+/// (r, r^2, ..., r^n)
+pub(crate) fn powers<F: PrimeField>(r: F, n: usize) -> Vec<F> {
+    (0..n)
+        .scan(F::one(), |power, _| {
+            *power *= r;
+            Some(*power)
+        })
+        .collect()
+}
+
+/// verification key holding digest of public parameters and r1cs shapes
+#[derive(Clone, Debug)]
+pub struct VerificationKey<F: PrimeField> {
+    pub(crate) digest: F,
+}
+
+impl<F: PrimeField> VerificationKey<F> {
+    /// digest H(pp, shapes) binding bases and every r1cs structure folded under key
+    pub(crate) fn new<C: TwistedEdwardsAffine<Scalar = F>>(
+        pp: &PedersenCommitment<C>,
+        shapes: &[&R1csStructure<C>],
+    ) -> Self
+    where
+        F: FftField,
+    {
+        let mut hasher = Digest::default();
+        hasher.update(b"vk");
+        hasher.update(&pp.digest().to_raw_bytes());
+        shapes
+            .iter()
+            .for_each(|shape| hasher.update(&shape.digest().to_raw_bytes()));
+        Self {
+            digest: hasher.finalize(),
+        }
+    }
+
+    /// digest of public parameters and r1cs shapes
+    pub fn digest(&self) -> F {
+        self.digest
     }
 }
 
-struct VerificationKey<F: PrimeField> {
-    digest: F,
+#[cfg(test)]
+mod tests {
+    use super::{challenge, Nifs};
+    use crate::error::Error;
+    use crate::public_param::PedersenCommitment;
+    use crate::tests::{example_r1cs, example_r1cs_instance, example_relaxed_r1cs_instance};
+
+    use jub_jub::JubjubAffine as Curve;
+    use rand_core::OsRng;
+    use zkstd::common::CurveGroup;
+
+    #[test]
+    fn challenge_test() {
+        let r1cs = example_r1cs::<Curve>();
+        let pp = PedersenCommitment::from_r1cs(&r1cs, OsRng);
+        let (prover, vk) = Nifs::k(pp, r1cs);
+        let pp = &prover.pp;
+        let instance = example_r1cs_instance::<Curve>(pp, 3).instance;
+        let relaxed_instance = example_relaxed_r1cs_instance::<Curve>(pp, 4).instance;
+        let commit_t = Curve::ADDITIVE_GENERATOR;
+        let other_commit_t = Curve::ADDITIVE_IDENTITY;
+
+        let r = challenge(&vk, &relaxed_instance, &instance, &commit_t);
+        let same_r = challenge(&vk, &relaxed_instance, &instance, &commit_t);
+        let other_r = challenge(&vk, &relaxed_instance, &instance, &other_commit_t);
+
+        assert_eq!(r, same_r);
+        assert_ne!(r, other_r)
+    }
+
+    #[test]
+    fn nifs_verify_test() {
+        let r1cs = example_r1cs::<Curve>();
+        let pp = PedersenCommitment::from_r1cs(&r1cs, OsRng);
+        let (prover, vk) = Nifs::k(pp, r1cs);
+
+        for i in 0..10 {
+            let r1cs_instance = example_r1cs_instance::<Curve>(&prover.pp, i);
+            let relaxed_r1cs_instance = example_relaxed_r1cs_instance::<Curve>(&prover.pp, i + 1);
+            let (instance, relaxed_instance) = (
+                r1cs_instance.instance.clone(),
+                relaxed_r1cs_instance.instance.clone(),
+            );
+            let (folded_r1cs_instance, commit_t) =
+                prover.prove(r1cs_instance, relaxed_r1cs_instance, OsRng);
+            let folded_instance = Nifs::verify(&vk, relaxed_instance, instance, commit_t);
+
+            assert!(folded_r1cs_instance.is_sat());
+            assert_eq!(folded_r1cs_instance.instance, folded_instance)
+        }
+    }
+
+    #[test]
+    fn relaxed_nifs_verify_test() {
+        let r1cs = example_r1cs::<Curve>();
+        let pp = PedersenCommitment::from_r1cs(&r1cs, OsRng);
+        let (prover, vk) = Nifs::k(pp, r1cs);
+        let mut relaxed_r1cs_instance = example_relaxed_r1cs_instance::<Curve>(&prover.pp, 1);
+
+        for i in 2..10 {
+            let other_r1cs_instance = example_relaxed_r1cs_instance::<Curve>(&prover.pp, i);
+            let (instance, relaxed_instance) = (
+                other_r1cs_instance.instance.clone(),
+                relaxed_r1cs_instance.instance.clone(),
+            );
+            let (folded_r1cs_instance, commit_t) =
+                prover.prove_relaxed(other_r1cs_instance, relaxed_r1cs_instance, OsRng);
+            let folded_instance = Nifs::verify_relaxed(&vk, relaxed_instance, instance, commit_t);
+
+            assert!(folded_r1cs_instance.is_sat());
+            assert_eq!(folded_r1cs_instance.instance, folded_instance);
+            relaxed_r1cs_instance = folded_r1cs_instance
+        }
+    }
+
+    #[test]
+    fn batch_nifs_verify_test() {
+        let r1cs = example_r1cs::<Curve>();
+        let pp = PedersenCommitment::from_r1cs(&r1cs, OsRng);
+        let (prover, vk) = Nifs::k(pp, r1cs);
+        let mut relaxed_r1cs_instance = example_relaxed_r1cs_instance::<Curve>(&prover.pp, 1);
+
+        for k in 1..5 {
+            let r1cs_instances = (0..k)
+                .map(|i| example_r1cs_instance::<Curve>(&prover.pp, i))
+                .collect::<Vec<_>>();
+            let instances = r1cs_instances
+                .iter()
+                .map(|r1cs_instance| r1cs_instance.instance.clone())
+                .collect::<Vec<_>>();
+            let relaxed_instance = relaxed_r1cs_instance.instance.clone();
+            let (folded_r1cs_instance, commit_t) =
+                prover.prove_batch(r1cs_instances, relaxed_r1cs_instance, OsRng);
+            let folded_instance = Nifs::verify_batch(
+                &vk,
+                relaxed_instance.clone(),
+                instances.clone(),
+                commit_t.clone(),
+            );
+
+            assert_eq!(commit_t.len(), 2 * k as usize - 1);
+            assert!(folded_r1cs_instance.is_sat());
+            assert_eq!(folded_instance, Ok(folded_r1cs_instance.instance.clone()));
+            assert_eq!(
+                Nifs::verify_batch(&vk, relaxed_instance, instances, commit_t[1..].to_vec()),
+                Err(Error::InvalidCrossTerms)
+            );
+            relaxed_r1cs_instance = folded_r1cs_instance
+        }
+    }
 }
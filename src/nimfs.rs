@@ -0,0 +1,256 @@
+use crate::ccs::{Cccs, CcsStructure, Instance as LcccsInstance, Lcccs};
+use crate::error::Error;
+use crate::nifs::{powers, VerificationKey};
+use crate::proof::MultifoldingProof;
+use crate::r1cs::Instance as CccsInstance;
+use crate::snark::{eq, eq_table, pad, SumcheckProof};
+use crate::transcript::{ChallengeTranscript, Transcript};
+
+use core::marker::PhantomData;
+use zkstd::common::{Group, TwistedEdwardsAffine};
+
+/// hypernova non-interactive multi-folding scheme folding running lcccs and k cccs into lcccs
+/// sumcheck proves Σ_x g(x) = Σ γ^j · vj over rows for
+/// g(x) = Σ γ^j · eq(rx, x) · M̃j · z̃0(x) + Σ γ^(t+k) · eq(β, x) · Σ ci · Π_{j ∈ Si} M̃j · z̃k(x)
+/// so one fold costs sumcheck of degree d + 1 independent of number of constraints per gate
+pub struct Nimfs<C: TwistedEdwardsAffine> {
+    marker: PhantomData<C>,
+}
+
+impl<C: TwistedEdwardsAffine> Nimfs<C> {
+    /// P(vk, (U, W), (uk, wk)) → ((U', W'), π)
+    pub fn prove(
+        vk: &VerificationKey<C::Scalar>,
+        lcccs: Lcccs<C>,
+        cccs: Vec<Cccs<C>>,
+    ) -> (Lcccs<C>, MultifoldingProof<C>) {
+        let ccs = &lcccs.ccs;
+        let (t, rows) = (ccs.t(), ccs.m.next_power_of_two());
+        let instances = cccs
+            .iter()
+            .map(|cccs| cccs.instance.clone())
+            .collect::<Vec<_>>();
+        let mut transcript = transcript(vk, &lcccs.instance, &instances);
+        let (gamma, beta) = challenges::<C>(&mut transcript, rows);
+
+        // eq(rx), eq(β), Mj · z0 and Mj · zk over rows
+        let Lcccs {
+            ccs,
+            instance,
+            witness,
+        } = lcccs;
+        let mut tables = vec![eq_table(&instance.r_x), eq_table(&beta)];
+        tables.extend(
+            ccs.prods(instance.u, &instance.x, &witness.w)
+                .into_iter()
+                .chain(cccs.iter().flat_map(|cccs| {
+                    let witness = &cccs.witness;
+                    ccs.prods(witness.one, &witness.x, &witness.w)
+                }))
+                .map(|prod| pad(&prod.0, rows)),
+        );
+        let coeffs = powers(gamma, t + cccs.len());
+        let (sumcheck, r_x, evaluations) = SumcheckProof::prove::<C, _>(
+            tables,
+            ccs.degree() + 1,
+            |v| g(&ccs, &coeffs, v[0], v[1], &v[2..]),
+            &mut transcript,
+        );
+
+        // σj = M̃j · z̃0(rx') and θkj = M̃j · z̃k(rx')
+        let sigmas = evaluations[2..2 + t].to_vec();
+        let thetas = evaluations[2 + t..]
+            .chunks(t)
+            .map(|theta| theta.to_vec())
+            .collect::<Vec<_>>();
+        let rho = absorb_evaluations::<C>(&mut transcript, &sigmas, &thetas);
+        let powers = powers(rho, cccs.len());
+
+        let witnesses = cccs
+            .into_iter()
+            .map(|cccs| cccs.witness)
+            .collect::<Vec<_>>();
+        let folded = Lcccs {
+            instance: instance.fold(&instances, &powers, r_x, &sigmas, &thetas),
+            witness: witness.fold(&witnesses, &powers),
+            ccs,
+        };
+        (
+            folded,
+            MultifoldingProof {
+                sumcheck,
+                sigmas,
+                thetas,
+            },
+        )
+    }
+
+    /// V(vk, U, (uk), π) → U'
+    pub fn verify(
+        vk: &VerificationKey<C::Scalar>,
+        ccs: &CcsStructure<C>,
+        instance: &LcccsInstance<C>,
+        instances: &[CccsInstance<C>],
+        proof: &MultifoldingProof<C>,
+    ) -> Result<LcccsInstance<C>, Error> {
+        let MultifoldingProof {
+            sumcheck,
+            sigmas,
+            thetas,
+        } = proof;
+        let (t, rows) = (ccs.t(), ccs.m.next_power_of_two());
+        let rounds = rows.trailing_zeros() as usize;
+        if instance.r_x.len() != rounds
+            || instance.v.len() != t
+            || sigmas.len() != t
+            || thetas.len() != instances.len()
+            || thetas.iter().any(|theta| theta.len() != t)
+        {
+            return Err(Error::InvalidSumcheck);
+        }
+        let mut transcript = transcript(vk, instance, instances);
+        let (gamma, beta) = challenges::<C>(&mut transcript, rows);
+
+        // Σ γ^j · vj reduces to g(rx')
+        let coeffs = powers(gamma, t + instances.len());
+        let claim = coeffs
+            .iter()
+            .zip(instance.v.iter())
+            .fold(C::Scalar::zero(), |sum, (gamma, v)| sum + *gamma * *v);
+        let (claim, r_x) =
+            sumcheck.verify::<C, _>(claim, rounds, ccs.degree() + 1, &mut transcript)?;
+        let evaluations = sigmas
+            .iter()
+            .chain(thetas.iter().flatten())
+            .copied()
+            .collect::<Vec<_>>();
+        let (eq_rx, eq_beta) = (eq(&instance.r_x, &r_x), eq(&beta, &r_x));
+        if claim != g(ccs, &coeffs, eq_rx, eq_beta, &evaluations) {
+            return Err(Error::InvalidSumcheck);
+        }
+
+        let rho = absorb_evaluations::<C>(&mut transcript, sigmas, thetas);
+        let powers = powers(rho, instances.len());
+        Ok(instance.fold(instances, &powers, r_x, sigmas, thetas))
+    }
+}
+
+/// γ^j · eq(rx) · σj + γ^(t+k) · eq(β) · Σ ci · Π_{j ∈ Si} θkj with σ and θk in evaluations
+fn g<C: TwistedEdwardsAffine>(
+    ccs: &CcsStructure<C>,
+    coeffs: &[C::Scalar],
+    eq_rx: C::Scalar,
+    eq_beta: C::Scalar,
+    evaluations: &[C::Scalar],
+) -> C::Scalar {
+    let t = ccs.t();
+    let (sigmas, thetas) = evaluations.split_at(t);
+    let linearized = sigmas
+        .iter()
+        .zip(coeffs.iter())
+        .fold(C::Scalar::zero(), |sum, (sigma, gamma)| {
+            sum + *gamma * *sigma
+        });
+    let committed = thetas
+        .chunks(t)
+        .zip(coeffs[t..].iter())
+        .fold(C::Scalar::zero(), |sum, (theta, gamma)| {
+            sum + *gamma * ccs.combine(theta)
+        });
+    eq_rx * linearized + eq_beta * committed
+}
+
+/// transcript bound to verification key and instances
+fn transcript<C: TwistedEdwardsAffine>(
+    vk: &VerificationKey<C::Scalar>,
+    instance: &LcccsInstance<C>,
+    instances: &[CccsInstance<C>],
+) -> Transcript {
+    let mut transcript = <Transcript as ChallengeTranscript<C>>::init(b"nimfs");
+    <Transcript as ChallengeTranscript<C>>::append_scalar(&mut transcript, b"vk", &vk.digest);
+    instance.absorb(&mut transcript);
+    instances
+        .iter()
+        .for_each(|instance| instance.absorb(&mut transcript));
+    transcript
+}
+
+/// γ and β over rows
+fn challenges<C: TwistedEdwardsAffine>(
+    transcript: &mut Transcript,
+    rows: usize,
+) -> (C::Scalar, Vec<C::Scalar>) {
+    let gamma = <Transcript as ChallengeTranscript<C>>::challenge_scalar(transcript, b"gamma");
+    let beta = (0..rows.trailing_zeros())
+        .map(|_| <Transcript as ChallengeTranscript<C>>::challenge_scalar(transcript, b"beta"))
+        .collect();
+    (gamma, beta)
+}
+
+/// absorb σ and θ and output folding challenge ρ
+fn absorb_evaluations<C: TwistedEdwardsAffine>(
+    transcript: &mut Transcript,
+    sigmas: &[C::Scalar],
+    thetas: &[Vec<C::Scalar>],
+) -> C::Scalar {
+    sigmas.iter().chain(thetas.iter().flatten()).for_each(|v| {
+        <Transcript as ChallengeTranscript<C>>::append_scalar(transcript, b"evaluation", v)
+    });
+    <Transcript as ChallengeTranscript<C>>::challenge_scalar(transcript, b"rho")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Nimfs;
+    use crate::ccs::{Cccs, CcsStructure, Lcccs};
+    use crate::nifs::VerificationKey;
+    use crate::public_param::PedersenCommitment;
+    use crate::tests::{example_r1cs, example_r1cs_witness};
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use rand_core::OsRng;
+
+    #[test]
+    fn multifolding_test() {
+        let r1cs = example_r1cs();
+        let ccs = CcsStructure::<Curve>::from(r1cs.clone());
+        let pp = PedersenCommitment::<Curve>::from_r1cs(&r1cs, OsRng);
+        let vk = VerificationKey {
+            digest: pp.digest(),
+        };
+        let cccs = |i| Cccs::new(&ccs, &example_r1cs_witness(i), &pp, OsRng).unwrap();
+        let mut lcccs = Lcccs::init(&ccs, 4);
+
+        for k in 1..4 {
+            let incoming = (0..k).map(|i| cccs(i + k)).collect::<Vec<_>>();
+            let instances = incoming
+                .iter()
+                .map(|cccs| cccs.instance.clone())
+                .collect::<Vec<_>>();
+            let instance = lcccs.instance.clone();
+            let (folded, proof) = Nimfs::prove(&vk, lcccs, incoming);
+            let folded_instance = Nimfs::verify(&vk, &ccs, &instance, &instances, &proof);
+
+            assert!(folded.is_sat());
+            assert_eq!(
+                folded.instance.commit_w,
+                pp.commit(&folded.witness.w, &folded.witness.r_w)
+            );
+            assert_eq!(folded_instance, Ok(folded.instance.clone()));
+
+            let mut invalid_proof = proof.clone();
+            invalid_proof.sigmas[0] += Scalar::one();
+            assert!(Nimfs::verify(&vk, &ccs, &instance, &instances, &invalid_proof).is_err());
+            lcccs = folded
+        }
+
+        // unsatisfying incoming instance fails sumcheck
+        let mut invalid_z = example_r1cs_witness(3);
+        invalid_z[0] += Scalar::one();
+        let incoming = vec![Cccs::new(&ccs, &invalid_z, &pp, OsRng).unwrap()];
+        let instances = vec![incoming[0].instance.clone()];
+        let instance = lcccs.instance.clone();
+        let (_, proof) = Nimfs::prove(&vk, lcccs, incoming);
+        assert!(Nimfs::verify(&vk, &ccs, &instance, &instances, &proof).is_err())
+    }
+}
@@ -0,0 +1,395 @@
+use crate::augmented_circuit::{
+    absorb_relaxed_instance, alloc_instance, alloc_relaxed_instance, alloc_selected, fold_instance,
+    trivial_instance, RelaxedInstanceWires,
+};
+use crate::constraint_system::ConstraintSystem;
+use crate::gadget::{
+    alloc, alloc_point, is_zero, mul, select, select_point, PointWires, PoseidonGadget,
+};
+use crate::hash::Poseidon;
+use crate::matrix::{DenseVectors, Element};
+use crate::nifs::{ivc_challenge, VerificationKey};
+use crate::r1cs::{Instance as R1csInstance, R1csStructure};
+use crate::relaxed_r1cs::Instance as RelaxedR1csInstance;
+use crate::step_circuit::StepCircuit;
+use crate::wire::Wire;
+
+use zkstd::common::{Group, PrimeField, Ring, TwistedEdwardsAffine};
+
+/// public io length of F'j which is H(vk, i+1, j, z0, zi+1, U⃗i+1)
+pub(crate) const NIVC_IO: usize = 1;
+
+/// non-uniform augmented circuit F'j for step circuit Fj selected by program counter zi[0] = j
+/// checks ui.x = H(vk, i, j', z0, zi, U⃗i) where ui is output by F'j', folds (U⃗i[j'], ui, T)
+/// into U⃗i+1[j'] and outputs H(vk, i+1, j, z0, Fj(zi), U⃗i+1)
+/// only one running instance is folded so cost doesn't depend on other step circuits
+/// except for hashing and selecting over running instances
+#[derive(Clone, Debug)]
+pub(crate) struct NivcCircuit<C: TwistedEdwardsAffine> {
+    /// verification key digest
+    pub(crate) digest: C::Scalar,
+    /// step counter
+    pub(crate) i: usize,
+    /// index j of executed step circuit
+    pub(crate) index: usize,
+    /// index j' of step circuit which output ui
+    pub(crate) last_index: usize,
+    pub(crate) z0: Vec<C::Scalar>,
+    pub(crate) zi: Vec<C::Scalar>,
+    /// running instances U⃗i one for each step circuit
+    pub(crate) relaxed_instances: Vec<RelaxedR1csInstance<C>>,
+    /// last step instance ui
+    pub(crate) instance: R1csInstance<C>,
+    /// commitment for cross term T
+    pub(crate) commit_t: C,
+}
+
+impl<C: TwistedEdwardsAffine> NivcCircuit<C> {
+    /// base case i = 0 running F'j over circuits step circuits with trivial instances
+    pub(crate) fn base(
+        digest: C::Scalar,
+        index: usize,
+        circuits: usize,
+        z0: Vec<C::Scalar>,
+    ) -> Self {
+        Self {
+            digest,
+            i: 0,
+            index,
+            last_index: 0,
+            zi: z0.clone(),
+            z0,
+            relaxed_instances: vec![trivial_instance(NIVC_IO); circuits],
+            instance: R1csInstance {
+                commit_w: C::ADDITIVE_IDENTITY,
+                x: DenseVectors(vec![C::Scalar::zero(); NIVC_IO]),
+            },
+            commit_t: C::ADDITIVE_IDENTITY,
+        }
+    }
+
+    /// running instances U⃗i+1 folded outside of circuit
+    pub(crate) fn folded_instances(&self) -> Vec<RelaxedR1csInstance<C>> {
+        if self.i == 0 {
+            vec![trivial_instance(NIVC_IO); self.relaxed_instances.len()]
+        } else {
+            let vk = VerificationKey {
+                digest: self.digest,
+            };
+            let relaxed_instance = &self.relaxed_instances[self.last_index];
+            let r = ivc_challenge(&vk, relaxed_instance, &self.instance, &self.commit_t);
+            let mut folded_instances = self.relaxed_instances.clone();
+            folded_instances[self.last_index] =
+                relaxed_instance.fold(self.instance.clone(), r, self.commit_t);
+            folded_instances
+        }
+    }
+
+    /// synthesize F'j with step circuit Fj and output zi+1
+    pub(crate) fn synthesize(
+        &self,
+        cs: &mut ConstraintSystem<C>,
+        step: &(impl StepCircuit<C> + ?Sized),
+    ) -> Vec<C::Scalar> {
+        let (zero, one) = (C::Scalar::zero(), C::Scalar::one());
+        let digest = cs.private_wire(self.digest);
+        let i = cs.private_wire(C::Scalar::from(self.i as u64));
+        let last_index = cs.private_wire(C::Scalar::from(self.last_index as u64));
+        let z0 = self
+            .z0
+            .iter()
+            .map(|z| cs.private_wire(*z))
+            .collect::<Vec<_>>();
+        let zi = self
+            .zi
+            .iter()
+            .map(|z| cs.private_wire(*z))
+            .collect::<Vec<_>>();
+        let relaxed_instances = self
+            .relaxed_instances
+            .iter()
+            .map(|instance| alloc_relaxed_instance(cs, instance))
+            .collect::<Vec<_>>();
+        let instance = alloc_instance(cs, &self.instance);
+        let commit_t = alloc_point(cs, &self.commit_t);
+
+        let is_base = is_zero(cs, i);
+        let not_base = vec![one.into(), Element(is_base, zero - one)];
+
+        // zi = z0 at base case
+        z0.iter().zip(zi.iter()).for_each(|(z0, zi)| {
+            cs.constrain(
+                vec![is_base.into()],
+                vec![(*zi).into(), Element(*z0, zero - one)],
+                vec![],
+            )
+        });
+
+        // program counter zi[0] = j selects Fj
+        let index = C::Scalar::from(self.index as u64);
+        cs.constrain(
+            vec![zi[0].into(), (zero - index).into()],
+            vec![one.into()],
+            vec![],
+        );
+
+        // ui.x = H(vk, i, j', z0, zi, U⃗i) after base case
+        let hash = hash_io(cs, digest, (i, last_index), &z0, &zi, &relaxed_instances);
+        cs.constrain(
+            not_base.clone(),
+            vec![instance.x[0].into(), Element(hash, zero - one)],
+            vec![],
+        );
+
+        // bk = 1 if j' = k and Σ bk = 1 after base case
+        let selectors = (0..relaxed_instances.len())
+            .map(|k| {
+                let k = C::Scalar::from(k as u64);
+                let diff = alloc(cs, vec![last_index.into(), (zero - k).into()]);
+                is_zero(cs, diff)
+            })
+            .collect::<Vec<_>>();
+        let mut sum = selectors
+            .iter()
+            .map(|b| (*b).into())
+            .collect::<Vec<Element<C::Scalar>>>();
+        sum.push((zero - one).into());
+        cs.constrain(not_base.clone(), sum, vec![]);
+
+        // fold (U⃗i[j'], ui, T) after base case
+        let relaxed_instance = select_instance(cs, &selectors, &relaxed_instances);
+        let folded_instance = fold_instance(
+            cs,
+            (digest, is_base),
+            (&relaxed_instance, &instance),
+            &commit_t,
+        );
+
+        // U⃗i+1[k] = bk ? U' : U⃗i[k] after base case
+        let folded_instances = relaxed_instances
+            .iter()
+            .zip(selectors.iter())
+            .map(|(relaxed_instance, b)| {
+                let mut select_scalar = |p, q| {
+                    let s = select(cs, *b, p, q);
+                    alloc_selected(cs, &not_base, vec![s.into()])
+                };
+                let u = select_scalar(folded_instance.u, relaxed_instance.u);
+                let x = folded_instance
+                    .x
+                    .iter()
+                    .zip(relaxed_instance.x.iter())
+                    .map(|(p, q)| select_scalar(*p, *q))
+                    .collect();
+                RelaxedInstanceWires {
+                    commit_w: select_point(
+                        cs,
+                        *b,
+                        &folded_instance.commit_w,
+                        &relaxed_instance.commit_w,
+                    ),
+                    commit_e: select_point(
+                        cs,
+                        *b,
+                        &folded_instance.commit_e,
+                        &relaxed_instance.commit_e,
+                    ),
+                    u,
+                    x,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // zi+1 = Fj(zi)
+        let zi1 = step.synthesize(cs, &zi);
+
+        // output H(vk, i+1, j, z0, zi+1, U⃗i+1)
+        let i1 = alloc(cs, vec![i.into(), one.into()]);
+        let index = alloc(cs, vec![index.into()]);
+        let hash = hash_io(cs, digest, (i1, index), &z0, &zi1, &folded_instances);
+        let value = cs.value(hash);
+        let output = cs.public_wire(value);
+        cs.equal_constraint(hash, output);
+
+        zi1.iter().map(|z| cs.value(*z)).collect()
+    }
+}
+
+/// r1cs structure of F'j for step circuit Fj among circuits and its witness length
+pub(crate) fn nivc_r1cs<C: TwistedEdwardsAffine>(
+    circuits: &[&dyn StepCircuit<C>],
+    index: usize,
+) -> (R1csStructure<C>, usize) {
+    let step = circuits[index];
+    assert!(step.arity() > 0, "program counter is required in z");
+    let mut cs = ConstraintSystem::new();
+    let mut z0 = vec![C::Scalar::zero(); step.arity()];
+    z0[0] = C::Scalar::from(index as u64);
+    let circuit = NivcCircuit::base(C::Scalar::zero(), index, circuits.len(), z0);
+    circuit.synthesize(&mut cs, step);
+    let structure = cs.structure();
+    let n = cs.assignment().len() - structure.l;
+    (structure, n)
+}
+
+/// index j of step circuit selected by program counter zi[0]
+pub(crate) fn program_counter<F: PrimeField>(zi: &[F], circuits: usize) -> usize {
+    (0..circuits)
+        .position(|j| F::from(j as u64) == zi[0])
+        .expect("program counter is out of range")
+}
+
+/// public io hash H(vk, i, j, z0, zi, U⃗) over running instances of all step circuits
+pub(crate) fn nivc_hash<C: TwistedEdwardsAffine>(
+    digest: C::Scalar,
+    (i, index): (usize, usize),
+    z0: &[C::Scalar],
+    zi: &[C::Scalar],
+    instances: &[RelaxedR1csInstance<C>],
+) -> C::Scalar {
+    let mut hasher = Poseidon::new(b"nivc");
+    hasher.absorb(digest);
+    hasher.absorb(C::Scalar::from(i as u64));
+    hasher.absorb(C::Scalar::from(index as u64));
+    z0.iter().chain(zi.iter()).for_each(|z| hasher.absorb(*z));
+    instances
+        .iter()
+        .for_each(|instance| instance.absorb_sponge(&mut hasher));
+    hasher.squeeze()
+}
+
+/// Σ bk · U⃗[k] for selectors bk with single one
+fn select_instance<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    selectors: &[Wire],
+    instances: &[RelaxedInstanceWires],
+) -> RelaxedInstanceWires {
+    let mut inner_product = |wires: Vec<Wire>| {
+        let elements = selectors
+            .iter()
+            .zip(wires)
+            .map(|(b, wire)| mul(cs, *b, wire).into())
+            .collect();
+        alloc(cs, elements)
+    };
+    // limbs stay in range since single selector is one
+    let mut select_limbs = |limbs: Vec<&[Wire]>| {
+        (0..limbs[0].len())
+            .map(|j| inner_product(limbs.iter().map(|limb| limb[j]).collect()))
+            .collect::<Vec<_>>()
+    };
+    let mut select_point = |points: Vec<&PointWires>| PointWires {
+        x: select_limbs(points.iter().map(|p| p.x.as_slice()).collect()),
+        y: select_limbs(points.iter().map(|p| p.y.as_slice()).collect()),
+    };
+    let commit_w = select_point(instances.iter().map(|u| &u.commit_w).collect());
+    let commit_e = select_point(instances.iter().map(|u| &u.commit_e).collect());
+    let u = inner_product(instances.iter().map(|u| u.u).collect());
+    let x = (0..NIVC_IO)
+        .map(|index| inner_product(instances.iter().map(|u| u.x[index]).collect()))
+        .collect();
+    RelaxedInstanceWires {
+        commit_w,
+        commit_e,
+        u,
+        x,
+    }
+}
+
+/// H(vk, i, j, z0, zi, U⃗) corresponding to `nivc_hash`
+fn hash_io<C: TwistedEdwardsAffine>(
+    cs: &mut ConstraintSystem<C>,
+    digest: Wire,
+    (i, index): (Wire, Wire),
+    z0: &[Wire],
+    zi: &[Wire],
+    instances: &[RelaxedInstanceWires],
+) -> Wire {
+    let mut hasher = PoseidonGadget::new(b"nivc");
+    hasher.absorb(cs, digest);
+    hasher.absorb(cs, i);
+    hasher.absorb(cs, index);
+    z0.iter()
+        .chain(zi.iter())
+        .for_each(|z| hasher.absorb(cs, *z));
+    instances
+        .iter()
+        .for_each(|instance| absorb_relaxed_instance(cs, &mut hasher, instance));
+    hasher.squeeze(cs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{nivc_hash, nivc_r1cs, NivcCircuit};
+    use crate::constraint_system::ConstraintSystem;
+    use crate::matrix::DenseVectors;
+    use crate::r1cs::Instance as R1csInstance;
+    use crate::step_circuit::StepCircuit;
+    use crate::tests::{CubeCircuit, DoubleCircuit};
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use zkstd::common::CurveGroup;
+
+    #[test]
+    fn nivc_circuit_test() {
+        let digest = Scalar::from(11);
+        let circuits: [&dyn StepCircuit<Curve>; 2] = [&CubeCircuit, &DoubleCircuit];
+        let z0 = vec![Scalar::zero(), Scalar::from(2)];
+
+        // base case runs F'0 with pc = 0
+        let circuit = NivcCircuit::<Curve>::base(digest, 0, 2, z0.clone());
+        let mut cs = ConstraintSystem::new();
+        let z1 = circuit.synthesize(&mut cs, circuits[0]);
+        let (r1cs, _) = nivc_r1cs(&circuits, 0);
+        let structure = cs.structure();
+        let u1 = circuit.folded_instances();
+
+        assert!(cs.is_sat());
+        assert_eq!((structure.m, structure.l), (r1cs.m, r1cs.l));
+        assert_eq!(z1, vec![Scalar::one(), Scalar::from(15)]);
+        assert_eq!(cs.assignment()[0], nivc_hash(digest, (1, 0), &z0, &z1, &u1));
+
+        // first step runs F'1 and folds instance of F'0 into U⃗[0] only
+        let circuit = NivcCircuit {
+            digest,
+            i: 1,
+            index: 1,
+            last_index: 0,
+            z0: z0.clone(),
+            zi: z1,
+            relaxed_instances: u1.clone(),
+            instance: R1csInstance {
+                commit_w: Curve::ADDITIVE_GENERATOR,
+                x: DenseVectors(vec![cs.assignment()[0]]),
+            },
+            commit_t: Curve::ADDITIVE_GENERATOR,
+        };
+        let mut cs = ConstraintSystem::new();
+        let z2 = circuit.synthesize(&mut cs, circuits[1]);
+        let (r1cs, _) = nivc_r1cs(&circuits, 1);
+        let u2 = circuit.folded_instances();
+
+        assert!(cs.is_sat());
+        assert_eq!(cs.structure().m, r1cs.m);
+        assert_eq!(z2, vec![Scalar::zero(), Scalar::from(30)]);
+        assert_ne!(u2[0], u1[0]);
+        assert_eq!(u2[1], u1[1]);
+        assert_eq!(cs.assignment()[0], nivc_hash(digest, (2, 1), &z0, &z2, &u2));
+
+        // program counter doesn't select executed circuit
+        let mut invalid_circuit = circuit.clone();
+        invalid_circuit.index = 0;
+        let mut cs = ConstraintSystem::new();
+        invalid_circuit.synthesize(&mut cs, circuits[0]);
+
+        assert!(!cs.is_sat());
+
+        // instance claimed to be output by other circuit
+        let mut invalid_circuit = circuit;
+        invalid_circuit.last_index = 1;
+        let mut cs = ConstraintSystem::new();
+        invalid_circuit.synthesize(&mut cs, circuits[1]);
+
+        assert!(!cs.is_sat())
+    }
+}
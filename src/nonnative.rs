@@ -0,0 +1,210 @@
+use std::cmp::Ordering;
+use zkstd::common::FftField;
+
+/// upper bound of folding challenge bits
+const MAX_CHALLENGE_BITS: usize = 128;
+
+/// bit length of field modulus
+pub(crate) fn modulus_bits<F: FftField>() -> usize {
+    let bytes = (F::zero() - F::one()).to_raw_bytes();
+    bytes
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |i| 8 * i + 8 - bytes[i].leading_zeros() as usize)
+}
+
+/// number of bits whose every value is below field modulus
+fn capacity<F: FftField>() -> usize {
+    modulus_bits::<F>() - 1
+}
+
+/// bits k of folding challenges so that r < 2^k is same integer in circuit field F and foreign
+/// field G and products r · limb with carries don't wrap around F
+pub(crate) fn challenge_bits<F: FftField, G: FftField>() -> usize {
+    MAX_CHALLENGE_BITS.min((capacity::<F>().min(capacity::<G>()) - 8) / 2)
+}
+
+/// bits L of each limb of element in G over F
+/// a + r · b - y - q · p for limbs stays below 2^(k + L + 4) with carries so L ≤ cap(F) - k - 6
+/// and each column of a · b + c - y - q · p over n ≤ 2^t limbs stays below 2^(2L + t + 3)
+/// with carries so L ≤ (cap(F) - t - 3) / 2
+pub(crate) fn limb_bits<F: FftField, G: FftField>() -> usize {
+    let mut limb_bits = capacity::<F>() - challenge_bits::<F, G>() - 6;
+    loop {
+        let n = to_bits(G::zero()).chunks(limb_bits).len();
+        let bound = (capacity::<F>() - log2(n) - 3) / 2;
+        if limb_bits <= bound {
+            return limb_bits;
+        }
+        limb_bits = bound
+    }
+}
+
+/// number of limbs n of element in G over F
+pub(crate) fn limb_count<F: FftField, G: FftField>() -> usize {
+    to_bits(G::zero()).chunks(limb_bits::<F, G>()).len()
+}
+
+/// smallest t such that n ≤ 2^t
+pub(crate) fn log2(n: usize) -> usize {
+    (usize::BITS - n.saturating_sub(1).leading_zeros()) as usize
+}
+
+/// little endian bits of canonical representation
+pub(crate) fn to_bits<F: FftField>(value: F) -> Vec<bool> {
+    value
+        .to_raw_bytes()
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+        .take(modulus_bits::<F>())
+        .collect()
+}
+
+/// element from little endian bits which are less than 512
+pub(crate) fn from_bits<F: FftField>(bits: &[bool]) -> F {
+    let mut bytes = [0u8; 64];
+    bits.iter()
+        .enumerate()
+        .for_each(|(i, bit)| bytes[i / 8] |= (*bit as u8) << (i % 8));
+    F::from_bytes_wide(&bytes)
+}
+
+/// 2^e for e less than 512
+pub(crate) fn power_of_two<F: FftField>(e: usize) -> F {
+    let mut bits = vec![false; e + 1];
+    bits[e] = true;
+    from_bits(&bits)
+}
+
+/// little endian limbs of L bits of foreign element in G as elements of F
+pub(crate) fn to_limbs<F: FftField, G: FftField>(value: G) -> Vec<F> {
+    to_bits(value)
+        .chunks(limb_bits::<F, G>())
+        .map(from_bits)
+        .collect()
+}
+
+/// foreign element Σ 2^(jL) · limbj from limbs
+pub(crate) fn from_limbs<F: FftField, G: FftField>(limbs: &[F]) -> G {
+    let shift = power_of_two::<G>(limb_bits::<F, G>());
+    limbs
+        .iter()
+        .rev()
+        .fold(G::zero(), |sum, limb| sum * shift + cast::<F, G>(*limb))
+}
+
+/// limbs of modulus p of G as elements of F
+pub(crate) fn modulus_limbs<F: FftField, G: FftField>() -> Vec<F> {
+    // p - 1 is even so adding one to lowest limb doesn't carry
+    let mut limbs = to_limbs::<F, G>(G::zero() - G::one());
+    limbs[0] += F::one();
+    limbs
+}
+
+/// value below both moduli of F and G mapped to G
+pub(crate) fn cast<F: FftField, G: FftField>(value: F) -> G {
+    from_bits(&to_bits(value))
+}
+
+/// little endian bits of ⌊(a · b + c) / p⌋ over integers for limbs of a, b and c of G over F
+/// and modulus p of G
+pub(crate) fn quotient<F: FftField, G: FftField>(a: &[F], b: &[F], c: &[F]) -> Vec<bool> {
+    let limb_bits = limb_bits::<F, G>();
+    let words = |limbs: &[F]| {
+        let bits = limbs
+            .iter()
+            .flat_map(|limb| to_bits(*limb).into_iter().take(limb_bits))
+            .collect::<Vec<_>>();
+        bits.chunks(64)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .rev()
+                    .fold(0u64, |word, bit| (word << 1) | *bit as u64)
+            })
+            .collect::<Vec<_>>()
+    };
+    let (a, b, c) = (words(a), words(b), words(c));
+
+    // x = a · b + c by schoolbook multiplication
+    let mut x = vec![0u64; a.len() + b.len() + c.len() + 1];
+    for (i, a) in a.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, b) in b.iter().enumerate() {
+            let sum = x[i + j] as u128 + *a as u128 * *b as u128 + carry;
+            x[i + j] = sum as u64;
+            carry = sum >> 64;
+        }
+        x[i + b.len()] = carry as u64;
+    }
+    let mut carry = 0u128;
+    for (i, x) in x.iter_mut().enumerate() {
+        let sum = *x as u128 + *c.get(i).unwrap_or(&0) as u128 + carry;
+        *x = sum as u64;
+        carry = sum >> 64;
+    }
+
+    // long division by p bit by bit from the top keeping remainder below p
+    let p = {
+        let mut p = words(&modulus_limbs::<F, G>());
+        p.push(0);
+        p
+    };
+    let mut rem = vec![0u64; p.len()];
+    let mut q = vec![false; 64 * x.len()];
+    for i in (0..q.len()).rev() {
+        let bit = (x[i / 64] >> (i % 64)) & 1;
+        let carry = rem.iter_mut().fold(bit, |carry, word| {
+            let top = *word >> 63;
+            *word = (*word << 1) | carry;
+            top
+        });
+        debug_assert_eq!(carry, 0);
+        if rem.iter().rev().cmp(p.iter().rev()) != Ordering::Less {
+            rem.iter_mut().zip(p.iter()).fold(false, |borrow, (r, p)| {
+                let (diff, b1) = r.overflowing_sub(*p);
+                let (diff, b2) = diff.overflowing_sub(borrow as u64);
+                *r = diff;
+                b1 || b2
+            });
+            q[i] = true
+        }
+    }
+    q
+}
+
+/// challenge truncated to lowest k bits
+pub(crate) fn truncate<F: FftField, G: FftField>(value: F) -> F {
+    from_bits(&to_bits(value)[..challenge_bits::<F, G>()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{challenge_bits, from_limbs, limb_bits, limb_count, modulus_limbs, to_limbs};
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use rand_core::OsRng;
+    use zkstd::common::{CurveGroup, Group};
+
+    type Base = <Curve as CurveGroup>::Range;
+
+    #[test]
+    fn limbs_test() {
+        let (k, l, n) = (
+            challenge_bits::<Scalar, Base>(),
+            limb_bits::<Scalar, Base>(),
+            limb_count::<Scalar, Base>(),
+        );
+        assert!(k > 0 && k < l);
+
+        for _ in 0..10 {
+            let value = Base::random(OsRng);
+            let limbs = to_limbs::<Scalar, Base>(value);
+
+            assert_eq!(limbs.len(), n);
+            assert_eq!(from_limbs::<Scalar, Base>(&limbs), value)
+        }
+        let modulus = modulus_limbs::<Scalar, Base>();
+        assert_eq!(from_limbs::<Scalar, Base>(&modulus), Base::zero())
+    }
+}
@@ -1,9 +1,148 @@
 use crate::r1cs::{Instance as R1csInstance, Witness as R1csWitness};
 use crate::relaxed_r1cs::{Instance as RelaxedR1csInstance, Witness as RelaxedR1csWitness};
+use crate::snark::{RelaxedR1csSnark, SumcheckProof};
 
+use std::ops::Range;
 use zkstd::common::TwistedEdwardsAffine;
 
-pub(crate) struct IvcProof<C: TwistedEdwardsAffine> {
+/// ivc proof Πi with running pair (Ui, Wi) and strict pair (ui, wi)
+#[derive(Clone, Debug)]
+pub struct IvcProof<C: TwistedEdwardsAffine> {
     pub(crate) upper_pair: (RelaxedR1csInstance<C>, RelaxedR1csWitness<C>),
-    pub(crate) lower_pair: (R1csInstance<C::Scalar>, R1csWitness<C>),
+    pub(crate) lower_pair: (R1csInstance<C>, R1csWitness<C>),
+}
+
+impl<C: TwistedEdwardsAffine> IvcProof<C> {
+    /// running relaxed instance Ui
+    pub fn upper_instance(&self) -> &RelaxedR1csInstance<C> {
+        &self.upper_pair.0
+    }
+
+    /// running relaxed witness Wi
+    pub fn upper_witness(&self) -> &RelaxedR1csWitness<C> {
+        &self.upper_pair.1
+    }
+
+    /// strict instance ui
+    pub fn lower_instance(&self) -> &R1csInstance<C> {
+        &self.lower_pair.0
+    }
+
+    /// strict witness wi
+    pub fn lower_witness(&self) -> &R1csWitness<C> {
+        &self.lower_pair.1
+    }
+}
+
+/// pcd proof of node proving za → zb over range [a, b) with running and node pairs
+#[derive(Clone, Debug)]
+pub struct PcdProof<C: TwistedEdwardsAffine> {
+    pub(crate) range: (usize, usize),
+    pub(crate) z_in: Vec<C::Scalar>,
+    pub(crate) z_out: Vec<C::Scalar>,
+    pub(crate) proof: IvcProof<C>,
+}
+
+impl<C: TwistedEdwardsAffine> PcdProof<C> {
+    /// range [a, b) of steps proven by node
+    pub fn range(&self) -> Range<usize> {
+        self.range.0..self.range.1
+    }
+
+    /// za
+    pub fn z_in(&self) -> &[C::Scalar] {
+        &self.z_in
+    }
+
+    /// zb
+    pub fn z_out(&self) -> &[C::Scalar] {
+        &self.z_out
+    }
+
+    /// running pair (U, W) and node pair (u, w)
+    pub fn proof(&self) -> &IvcProof<C> {
+        &self.proof
+    }
+}
+
+/// non-uniform ivc proof Πi with running pair (U⃗i[k], W⃗i[k]) for each step circuit Fk
+/// and strict pair (ui, wi) of F'j which output it
+#[derive(Clone, Debug)]
+pub struct NivcProof<C: TwistedEdwardsAffine> {
+    pub(crate) upper_pairs: Vec<(RelaxedR1csInstance<C>, RelaxedR1csWitness<C>)>,
+    pub(crate) lower_pair: (R1csInstance<C>, R1csWitness<C>),
+    pub(crate) index: usize,
+}
+
+impl<C: TwistedEdwardsAffine> NivcProof<C> {
+    /// index j of step circuit which output ui
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// running relaxed instance U⃗i[k] of step circuit Fk
+    pub fn upper_instance(&self, k: usize) -> &RelaxedR1csInstance<C> {
+        &self.upper_pairs[k].0
+    }
+
+    /// running relaxed witness W⃗i[k] of step circuit Fk
+    pub fn upper_witness(&self, k: usize) -> &RelaxedR1csWitness<C> {
+        &self.upper_pairs[k].1
+    }
+
+    /// strict instance ui
+    pub fn lower_instance(&self) -> &R1csInstance<C> {
+        &self.lower_pair.0
+    }
+
+    /// strict witness wi
+    pub fn lower_witness(&self) -> &R1csWitness<C> {
+        &self.lower_pair.1
+    }
+}
+
+/// compressed ivc proof with (Ui, ui, T) and snark for Ui+1 folded from them
+#[derive(Clone, Debug)]
+pub struct CompressedProof<C: TwistedEdwardsAffine> {
+    pub(crate) upper_instance: RelaxedR1csInstance<C>,
+    pub(crate) lower_instance: R1csInstance<C>,
+    pub(crate) commit_t: C,
+    pub(crate) snark: RelaxedR1csSnark<C>,
+}
+
+impl<C: TwistedEdwardsAffine> CompressedProof<C> {
+    /// running relaxed instance Ui
+    pub fn upper_instance(&self) -> &RelaxedR1csInstance<C> {
+        &self.upper_instance
+    }
+
+    /// strict instance ui
+    pub fn lower_instance(&self) -> &R1csInstance<C> {
+        &self.lower_instance
+    }
+
+    /// commitment of cross term T
+    pub fn commit_t(&self) -> C {
+        self.commit_t
+    }
+}
+
+/// multi-folding proof with sumcheck over rows and evaluations at its challenges
+#[derive(Clone, Debug)]
+pub struct MultifoldingProof<C: TwistedEdwardsAffine> {
+    pub(crate) sumcheck: SumcheckProof<C::Scalar>,
+    pub(crate) sigmas: Vec<C::Scalar>,
+    pub(crate) thetas: Vec<Vec<C::Scalar>>,
+}
+
+impl<C: TwistedEdwardsAffine> MultifoldingProof<C> {
+    /// σj = M̃j · z̃0(rx') of running instance
+    pub fn sigmas(&self) -> &[C::Scalar] {
+        &self.sigmas
+    }
+
+    /// θkj = M̃j · z̃k(rx') of each incoming instance
+    pub fn thetas(&self) -> &[Vec<C::Scalar>] {
+        &self.thetas
+    }
 }
@@ -0,0 +1,273 @@
+use crate::ccs::{Cccs, CcsStructure, RelaxedCcs};
+use crate::error::Error;
+use crate::matrix::DenseVectors;
+use crate::nifs::{powers, VerificationKey};
+use crate::public_param::PedersenCommitment;
+use crate::r1cs::{Instance as CccsInstance, Witness as CccsWitness};
+use crate::relaxed_r1cs::{Instance as RelaxedInstance, Witness as RelaxedWitness};
+use crate::transcript::{ChallengeTranscript, Transcript};
+
+use core::marker::PhantomData;
+use zkstd::common::{Group, PrimeField, Ring, RngCore, TwistedEdwardsAffine};
+
+/// protostar style folding scheme for ccs of degree d folding cccs into relaxed ccs
+/// f(Z1 + X · Z2) = Σ X^k · Tk over homogenized gates has T0 = E and Td = 0
+/// so prover commits d - 1 cross terms and E' = E + Σ r^k · Tk
+pub struct Protostar<C: TwistedEdwardsAffine> {
+    marker: PhantomData<C>,
+}
+
+impl<C: TwistedEdwardsAffine> Protostar<C> {
+    /// P(pp, vk, (U, W), (u, w)) → ((U', W'), (T1, ..., Td-1))
+    pub fn prove(
+        pp: &PedersenCommitment<C>,
+        vk: &VerificationKey<C::Scalar>,
+        cccs: Cccs<C>,
+        relaxed_ccs: RelaxedCcs<C>,
+        mut rng: impl RngCore,
+    ) -> (RelaxedCcs<C>, Vec<C>) {
+        let t = compute_cross_terms(&relaxed_ccs, &cccs);
+        let r_t = t
+            .iter()
+            .map(|_| C::Scalar::random(&mut rng))
+            .collect::<Vec<_>>();
+        let commit_t = t
+            .iter()
+            .zip(r_t.iter())
+            .map(|(t, r_t)| pp.commit(t, r_t))
+            .collect::<Vec<_>>();
+        let RelaxedCcs {
+            ccs,
+            instance,
+            witness,
+        } = relaxed_ccs;
+        let r = challenge(vk, &instance, &cccs.instance, &commit_t);
+
+        (
+            RelaxedCcs {
+                instance: fold_instance(&instance, cccs.instance, r, &commit_t),
+                witness: fold_witness(&witness, cccs.witness, r, (t, r_t)),
+                ccs,
+            },
+            commit_t,
+        )
+    }
+
+    /// V(vk, U, u, (T1, ..., Td-1)) → U'
+    pub fn verify(
+        vk: &VerificationKey<C::Scalar>,
+        ccs: &CcsStructure<C>,
+        relaxed_instance: RelaxedInstance<C>,
+        instance: CccsInstance<C>,
+        commit_t: &[C],
+    ) -> Result<RelaxedInstance<C>, Error> {
+        if commit_t.len() != ccs.degree().saturating_sub(1) {
+            return Err(Error::InvalidCrossTerms);
+        }
+        let r = challenge(vk, &relaxed_instance, &instance, commit_t);
+        Ok(fold_instance(&relaxed_instance, instance, r, commit_t))
+    }
+}
+
+/// U' = (W + r · W2, E + Σ r^k · Tk, u + r, x + r · x2) for cccs u2 = 1
+/// E' has no cross term for degree 1 since f is linear
+fn fold_instance<C: TwistedEdwardsAffine>(
+    relaxed_instance: &RelaxedInstance<C>,
+    instance: CccsInstance<C>,
+    r: C::Scalar,
+    commit_t: &[C],
+) -> RelaxedInstance<C> {
+    let commit_e = commit_t
+        .iter()
+        .zip(powers(r, commit_t.len()))
+        .fold(relaxed_instance.commit_e.to_extended(), |sum, (t, rk)| {
+            sum + *t * rk
+        });
+    RelaxedInstance {
+        commit_w: (relaxed_instance.commit_w + instance.commit_w * r).into(),
+        commit_e: commit_e.into(),
+        u: relaxed_instance.u + r,
+        x: relaxed_instance.x.clone() + instance.x * r,
+    }
+}
+
+/// W' = (W + r · W2, E + Σ r^k · Tk) with randomness folded in same way
+fn fold_witness<C: TwistedEdwardsAffine>(
+    relaxed_witness: &RelaxedWitness<C>,
+    witness: CccsWitness<C>,
+    r: C::Scalar,
+    (t, r_t): (Vec<DenseVectors<C::Scalar>>, Vec<C::Scalar>),
+) -> RelaxedWitness<C> {
+    let powers = powers(r, t.len());
+    let (e, r_e) = t.into_iter().zip(r_t).zip(powers).fold(
+        (relaxed_witness.e.clone(), relaxed_witness.r_e),
+        |(e, r_e), ((t, r_t), rk)| (e + t * rk, r_e + r_t * rk),
+    );
+    RelaxedWitness {
+        w: relaxed_witness.w.clone() + witness.w * r,
+        e,
+        r_w: relaxed_witness.r_w + witness.r_w * r,
+        r_e,
+    }
+}
+
+/// Tk as coefficients of X^k in Σ ci · (u1 + X)^(d - |Si|) · Π_{j ∈ Si} (Mj · Z1 + X · Mj · Z2)
+/// for k in 1..d where Z1 is relaxed ccs and Z2 is cccs with u2 = 1
+pub(crate) fn compute_cross_terms<C: TwistedEdwardsAffine>(
+    relaxed_ccs: &RelaxedCcs<C>,
+    cccs: &Cccs<C>,
+) -> Vec<DenseVectors<C::Scalar>> {
+    let ccs = &relaxed_ccs.ccs;
+    let (m, d) = (ccs.m, ccs.degree());
+    let u1 = relaxed_ccs.instance.u;
+    let prods1 = ccs.prods(u1, &relaxed_ccs.instance.x, &relaxed_ccs.witness.w);
+    let prods2 = ccs.prods(cccs.witness.one, &cccs.witness.x, &cccs.witness.w);
+
+    let mut t = vec![DenseVectors(vec![C::Scalar::zero(); m]); d.saturating_sub(1)];
+    for row in 0..m {
+        let mut poly = vec![C::Scalar::zero(); d + 1];
+        for (multiset, c) in ccs.multisets.iter().zip(ccs.constants.iter()) {
+            let term = (multiset.len()..d)
+                .map(|_| (u1, C::Scalar::one()))
+                .chain(multiset.iter().map(|j| (prods1[*j][row], prods2[*j][row])))
+                .fold(vec![*c], |term, linear| mul_linear(&term, linear));
+            poly.iter_mut().zip(term).for_each(|(p, t)| *p += t);
+        }
+        t.iter_mut()
+            .zip(poly[1..d].iter())
+            .for_each(|(tk, coeff)| tk[row] = *coeff);
+    }
+    t
+}
+
+/// p(X) · (a + X · b) in coefficient form
+fn mul_linear<F: PrimeField>(p: &[F], (a, b): (F, F)) -> Vec<F> {
+    let mut prod = vec![F::zero(); p.len() + 1];
+    p.iter().enumerate().for_each(|(i, coeff)| {
+        prod[i] += *coeff * a;
+        prod[i + 1] += *coeff * b;
+    });
+    prod
+}
+
+/// folding challenge r ← p(vk, U, u, (T1, ..., Td-1))
+fn challenge<C: TwistedEdwardsAffine>(
+    vk: &VerificationKey<C::Scalar>,
+    relaxed_instance: &RelaxedInstance<C>,
+    instance: &CccsInstance<C>,
+    commit_t: &[C],
+) -> C::Scalar {
+    let mut transcript = <Transcript as ChallengeTranscript<C>>::init(b"protostar");
+    <Transcript as ChallengeTranscript<C>>::append_scalar(&mut transcript, b"vk", &vk.digest);
+    relaxed_instance.absorb(&mut transcript);
+    instance.absorb(&mut transcript);
+    commit_t
+        .iter()
+        .for_each(|commit_t| transcript.append_point(b"commit_t", commit_t));
+    <Transcript as ChallengeTranscript<C>>::challenge_scalar(&mut transcript, b"r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Protostar;
+    use crate::ccs::{Cccs, CcsStructure, Gate, RelaxedCcs};
+    use crate::error::Error;
+    use crate::linear_combination::LinearCombination;
+    use crate::nifs::VerificationKey;
+    use crate::public_param::PedersenCommitment;
+    use crate::tests::{example_r1cs, example_r1cs_witness};
+    use crate::wire::Wire;
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use rand_core::OsRng;
+    use zkstd::common::{CurveGroup, PrimeField};
+
+    fn fold(ccs: &CcsStructure<Curve>, assignments: Vec<Vec<Scalar>>, n: usize) {
+        let pp = PedersenCommitment::<Curve>::new(n.max(ccs.m()), OsRng);
+        let vk = VerificationKey {
+            digest: pp.digest(),
+        };
+        let mut relaxed_ccs = RelaxedCcs::init(ccs, n);
+        for z in assignments {
+            let cccs = Cccs::new(ccs, &z, &pp, OsRng).unwrap();
+            let (instance, relaxed_instance) =
+                (cccs.instance.clone(), relaxed_ccs.instance.clone());
+            let (folded, commit_t) = Protostar::prove(&pp, &vk, cccs, relaxed_ccs, OsRng);
+            let folded_instance = Protostar::verify(
+                &vk,
+                ccs,
+                relaxed_instance.clone(),
+                instance.clone(),
+                &commit_t,
+            );
+
+            assert_eq!(commit_t.len(), ccs.degree() - 1);
+            assert!(folded.is_sat());
+            assert_eq!(folded_instance, Ok(folded.instance.clone()));
+            assert_eq!(
+                folded.instance.commit_e,
+                pp.commit(&folded.witness.e, &folded.witness.r_e)
+            );
+            let extra_t = [commit_t, vec![Curve::ADDITIVE_IDENTITY]].concat();
+            assert_eq!(
+                Protostar::verify(&vk, ccs, relaxed_instance, instance, &extra_t),
+                Err(Error::InvalidCrossTerms)
+            );
+            relaxed_ccs = folded
+        }
+    }
+
+    #[test]
+    fn r1cs_folding_test() {
+        let ccs = CcsStructure::from(example_r1cs::<Curve>());
+        fold(&ccs, (1..5).map(example_r1cs_witness).collect(), 4)
+    }
+
+    #[test]
+    fn sbox_folding_test() {
+        // x^5 + 2 · x · y - z = 0 and x + y = w mixing degree 5, 2 and 1 terms
+        let (x, y) = (Wire::witness(0), Wire::witness(1));
+        let (z, w) = (Wire::instance(0), Wire::instance(1));
+        let (one, two) = (Scalar::one(), Scalar::from(2));
+        let gates = [
+            Gate::new()
+                .term(one, vec![x.into(); 5])
+                .term(two, vec![x.into(), y.into()])
+                .term(Scalar::zero() - one, vec![z.into()]),
+            Gate::new()
+                .term(one, vec![LinearCombination::from(x) + y])
+                .term(Scalar::zero() - one, vec![w.into()]),
+        ];
+        let ccs = CcsStructure::<Curve>::from_gates(2, &gates);
+        let z = |x: u64, y: u64| {
+            let (x, y) = (Scalar::from(x), Scalar::from(y));
+            vec![x.square().square() * x + two * x * y, x + y, x, y]
+        };
+
+        assert_eq!((ccs.m(), ccs.degree()), (2, 5));
+        assert!(
+            Cccs::new(&ccs, &z(3, 4), &PedersenCommitment::new(4, OsRng), OsRng)
+                .unwrap()
+                .is_sat()
+        );
+        fold(&ccs, (1..5).map(|i| z(i, i + 2)).collect(), 2)
+    }
+
+    #[test]
+    fn linear_folding_test() {
+        // x + 2 · y - z = 0 has no cross term and E stays zero
+        let (x, y, z) = (Wire::witness(0), Wire::witness(1), Wire::instance(0));
+        let (one, two) = (Scalar::one(), Scalar::from(2));
+        let gates = [Gate::new()
+            .term(one, vec![x * one + y * two])
+            .term(Scalar::zero() - one, vec![z.into()])];
+        let ccs = CcsStructure::<Curve>::from_gates(1, &gates);
+        let z = |x: u64, y: u64| {
+            let (x, y) = (Scalar::from(x), Scalar::from(y));
+            vec![x + two * y, x, y]
+        };
+
+        assert_eq!((ccs.m(), ccs.degree()), (1, 1));
+        fold(&ccs, (1..5).map(|i| z(i, i + 3)).collect(), 2)
+    }
+}
@@ -1,105 +1,875 @@
+use crate::augmented_circuit::{augmented_r1cs, AugmentedCircuit};
+use crate::constraint_system::ConstraintSystem;
 use crate::matrix::DenseVectors;
-use crate::proof::IvcProof;
+use crate::merge_circuit::{merge_r1cs, MergeChild, MergeCircuit};
+use crate::nifs::{
+    batch_challenge, challenge, ivc_challenge, pcd_challenge, relaxed_challenge, VerificationKey,
+};
+use crate::nivc_circuit::{nivc_r1cs, program_counter, NivcCircuit};
+use crate::proof::{CompressedProof, IvcProof, NivcProof, PcdProof};
 use crate::public_param::PedersenCommitment;
-use crate::r1cs::{R1csInstance as R1cs, R1csStructure};
-use crate::relaxed_r1cs::RelaxedR1csInstance as RelaxedR1cs;
+use crate::r1cs::{Instance as R1csInstance, R1csInstance as R1cs, R1csStructure};
+use crate::relaxed_r1cs::{
+    Instance as RelaxedR1csInstance, RelaxedR1csInstance as RelaxedR1cs,
+    Witness as RelaxedR1csWitness,
+};
+use crate::snark::{self, RelaxedR1csSnark};
+use crate::step_circuit::StepCircuit;
 
-use zkstd::common::{Ring, TwistedEdwardsAffine};
+use std::iter;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use zkstd::common::{Group, Ring, RngCore, TwistedEdwardsAffine};
 
-pub(crate) struct Prover<C: TwistedEdwardsAffine> {
+/// ivc prover holding prover key pk = (pp, F', vk) and step counter
+/// F' folds commitments of running instance in circuit by non-native curve arithmetic
+pub struct Prover<C: TwistedEdwardsAffine> {
     pub(crate) pp: PedersenCommitment<C>,
     pub(crate) f: R1csStructure<C>,
+    pub(crate) vk: VerificationKey<C::Scalar>,
     pub(crate) i: usize,
 }
 
 impl<C: TwistedEdwardsAffine> Prover<C> {
-    pub(crate) fn new(pp: PedersenCommitment<C>, f: R1csStructure<C>) -> Self {
-        Self { pp, f, i: 0 }
+    /// prover for r1cs structure f
+    pub fn new(pp: PedersenCommitment<C>, f: R1csStructure<C>) -> Self {
+        let vk = VerificationKey::new(&pp, &[&f]);
+        Self { pp, f, vk, i: 0 }
     }
 
-    pub(crate) fn recurse(
+    /// prover for F' derived from step circuit F
+    pub fn from_circuit(pp: PedersenCommitment<C>, circuit: &impl StepCircuit<C>) -> Self {
+        Self::new(pp, augmented_r1cs(circuit))
+    }
+
+    /// verification key
+    pub fn vk(&self) -> &VerificationKey<C::Scalar> {
+        &self.vk
+    }
+
+    /// number of steps proven so far
+    pub fn steps(&self) -> usize {
+        self.i
+    }
+
+    /// P(pk, (i, z0, zi), ωi, Πi) → (zi+1, Πi+1) where Πi is None at base case
+    pub fn recurse(
+        &mut self,
+        circuit: &impl StepCircuit<C>,
+        z0: &[C::Scalar],
+        zi: &[C::Scalar],
+        πi: Option<IvcProof<C>>,
+        mut rng: impl RngCore,
+    ) -> (Vec<C::Scalar>, IvcProof<C>) {
+        let digest = self.vk.digest;
+        let (augmented_circuit, folded_pair) = if self.i == 0 {
+            (AugmentedCircuit::base(digest, z0.to_vec()), None)
+        } else {
+            // fold previous step into running instance
+            let πi = πi.expect("previous proof is required after base case");
+            let (relaxed_instance, instance) = (πi.upper_pair.0.clone(), πi.lower_pair.0.clone());
+            let (folded_r1cs, commit_t) = self.fold(πi, &mut rng);
+            let augmented_circuit = AugmentedCircuit {
+                digest,
+                i: self.i,
+                z0: z0.to_vec(),
+                zi: zi.to_vec(),
+                relaxed_instance,
+                instance,
+                commit_t,
+            };
+            (
+                augmented_circuit,
+                Some((folded_r1cs.instance, folded_r1cs.witness)),
+            )
+        };
+
+        // trace F' for ui+1 = (H(vk, i+1, z0, zi+1, Ui+1))
+        let mut cs = ConstraintSystem::new();
+        let zi1 = augmented_circuit.synthesize(&mut cs, circuit);
+        assert_eq!(zi1, circuit.output(zi));
+        let z = cs.assignment();
+
+        // base case starts from trivial relaxed r1cs
+        let (upper_instance, upper_witness) = folded_pair.unwrap_or_else(|| {
+            let n = z.len() - self.f.l;
+            (
+                RelaxedR1csInstance::init(self.f.clone()),
+                RelaxedR1csWitness::init(self.f.clone(), n),
+            )
+        });
+        debug_assert_eq!(upper_instance, augmented_circuit.folded_instance());
+        let R1cs {
+            instance, witness, ..
+        } = R1cs::new(&self.f, &z, &self.pp, rng).expect("assignment matches structure");
+        self.i += 1;
+
+        (
+            zi1,
+            IvcProof {
+                upper_pair: (upper_instance, upper_witness),
+                lower_pair: (instance, witness),
+            },
+        )
+    }
+
+    /// compress Πi into (Ui, ui, T) and snark proving knowledge of witnesses for folded Ui+1
+    pub fn compress(&self, πi: IvcProof<C>, mut rng: impl RngCore) -> CompressedProof<C> {
+        let (upper_instance, lower_instance) = (πi.upper_pair.0.clone(), πi.lower_pair.0.clone());
+        let (folded_r1cs, commit_t) = self.fold(πi, &mut rng);
+        let mut transcript = snark::transcript(&self.vk, &folded_r1cs.instance);
+        let snark = RelaxedR1csSnark::prove(&self.pp, &folded_r1cs, &mut transcript, rng);
+
+        CompressedProof {
+            upper_instance,
+            lower_instance,
+            commit_t,
+            snark,
+        }
+    }
+
+    /// fold lower pair of Πi into upper pair
+    pub fn fold(&self, πi: IvcProof<C>, rng: impl RngCore) -> (RelaxedR1cs<C>, C) {
+        let IvcProof {
+            upper_pair,
+            lower_pair,
+        } = πi;
+        let relaxed_r1cs = RelaxedR1cs {
+            r1cs: self.f.clone(),
+            instance: upper_pair.0,
+            witness: upper_pair.1,
+        };
+        let r1cs = R1cs {
+            r1cs: self.f.clone(),
+            instance: lower_pair.0,
+            witness: lower_pair.1,
+        };
+        fold_r1cs(&self.pp, &self.vk, r1cs, relaxed_r1cs, rng)
+    }
+
+    /// fold r1cs into relaxed r1cs and output folded relaxed r1cs with commitment T
+    pub fn prove(
         &self,
-        z0: Vec<C::Scalar>,
-        zi: Vec<C::Scalar>,
-        πi: IvcProof<C>,
-    ) -> IvcProof<C> {
-        if self.i == 0 {}
-        πi
-    }
-
-    pub(crate) fn prove(&self, r1cs: R1cs<C>, relaxed_r1cs: RelaxedR1cs<C>) -> RelaxedR1cs<C> {
-        let lc_random = C::Scalar::one();
-        let (w0, x0) = (r1cs.witness.w.clone(), r1cs.instance.x.clone());
-        let (w1, x1) = (
-            relaxed_r1cs.witness.w.clone(),
-            relaxed_r1cs.instance.x.clone(),
-        );
-        let u2 = relaxed_r1cs.instance.u;
-        let t = self.compute_cross_term(w0, x0, w1, x1, u2);
-        let commit_t = self.pp.commit(&t, &lc_random);
+        r1cs: R1cs<C>,
+        relaxed_r1cs: RelaxedR1cs<C>,
+        rng: impl RngCore,
+    ) -> (RelaxedR1cs<C>, C) {
+        fold_r1cs_with(
+            &self.pp,
+            r1cs,
+            relaxed_r1cs,
+            |relaxed_instance, instance, commit_t| {
+                challenge(&self.vk, relaxed_instance, instance, commit_t)
+            },
+            rng,
+        )
+    }
+
+    /// fold k r1cs into relaxed r1cs under one challenge r with U' = U + Σ r^i · ui
+    /// absorbs every instance into one transcript and commits each cross term Td for d in
+    /// 1..2k once, while matrix products are computed once per instance
+    pub fn prove_batch(
+        &self,
+        r1cs: Vec<R1cs<C>>,
+        relaxed_r1cs: RelaxedR1cs<C>,
+        mut rng: impl RngCore,
+    ) -> (RelaxedR1cs<C>, Vec<C>) {
+        let t = compute_batch_cross_terms(&self.f, &r1cs, &relaxed_r1cs);
+        let r_t = t
+            .iter()
+            .map(|_| C::Scalar::random(&mut rng))
+            .collect::<Vec<_>>();
+        let commit_t = t
+            .iter()
+            .zip(r_t.iter())
+            .map(|(t, r_t)| self.pp.commit(t, r_t))
+            .collect::<Vec<_>>();
+        let (instances, witnesses): (Vec<_>, Vec<_>) = r1cs
+            .into_iter()
+            .map(|r1cs| (r1cs.instance, r1cs.witness))
+            .unzip();
+        let lc_random = batch_challenge(&self.vk, &relaxed_r1cs.instance, &instances, &commit_t);
         let folded_instance = relaxed_r1cs
             .instance
-            .fold(r1cs.instance, lc_random, commit_t);
-        let folded_witness = relaxed_r1cs.witness.fold(r1cs.witness, lc_random, t);
+            .fold_batch(instances, lc_random, &commit_t);
+        let folded_witness = relaxed_r1cs
+            .witness
+            .fold_batch(witnesses, lc_random, t, r_t);
 
+        (
+            RelaxedR1cs {
+                r1cs: self.f.clone(),
+                instance: folded_instance,
+                witness: folded_witness,
+            },
+            commit_t,
+        )
+    }
+
+    /// fold relaxed r1cs into relaxed r1cs and output folded relaxed r1cs with commitment T
+    pub fn prove_relaxed(
+        &self,
+        r1cs: RelaxedR1cs<C>,
+        relaxed_r1cs: RelaxedR1cs<C>,
+        rng: impl RngCore,
+    ) -> (RelaxedR1cs<C>, C) {
+        fold_relaxed_r1cs_with(
+            &self.pp,
+            r1cs,
+            relaxed_r1cs,
+            |relaxed_instance, instance, commit_t| {
+                relaxed_challenge(&self.vk, relaxed_instance, instance, commit_t)
+            },
+            rng,
+        )
+    }
+}
+
+/// fold relaxed r1cs into relaxed r1cs with challenge r ← p(U2, U1, T) squeezed by given
+/// transcript
+pub(crate) fn fold_relaxed_r1cs_with<C: TwistedEdwardsAffine>(
+    pp: &PedersenCommitment<C>,
+    r1cs: RelaxedR1cs<C>,
+    relaxed_r1cs: RelaxedR1cs<C>,
+    challenge: impl FnOnce(&RelaxedR1csInstance<C>, &RelaxedR1csInstance<C>, &C) -> C::Scalar,
+    mut rng: impl RngCore,
+) -> (RelaxedR1cs<C>, C) {
+    let (w0, x0, u1) = (
+        r1cs.witness.w.clone(),
+        r1cs.instance.x.clone(),
+        r1cs.instance.u,
+    );
+    let (w1, x1, u2) = (
+        relaxed_r1cs.witness.w.clone(),
+        relaxed_r1cs.instance.x.clone(),
+        relaxed_r1cs.instance.u,
+    );
+    let t = compute_cross_term(&relaxed_r1cs.r1cs, w0, x0, u1, w1, x1, u2);
+    let r_t = C::Scalar::random(&mut rng);
+    let commit_t = pp.commit(&t, &r_t);
+    let lc_random = challenge(&relaxed_r1cs.instance, &r1cs.instance, &commit_t);
+    let folded_instance = relaxed_r1cs
+        .instance
+        .fold_relaxed(r1cs.instance, lc_random, commit_t);
+    let folded_witness = relaxed_r1cs
+        .witness
+        .fold_relaxed(r1cs.witness, lc_random, t, r_t);
+
+    (
         RelaxedR1cs {
-            r1cs: self.f.clone(),
+            r1cs: relaxed_r1cs.r1cs,
             instance: folded_instance,
             witness: folded_witness,
+        },
+        commit_t,
+    )
+}
+
+/// fold r1cs into relaxed r1cs over structure of relaxed r1cs
+pub(crate) fn fold_r1cs<C: TwistedEdwardsAffine>(
+    pp: &PedersenCommitment<C>,
+    vk: &VerificationKey<C::Scalar>,
+    r1cs: R1cs<C>,
+    relaxed_r1cs: RelaxedR1cs<C>,
+    rng: impl RngCore,
+) -> (RelaxedR1cs<C>, C) {
+    fold_r1cs_with(
+        pp,
+        r1cs,
+        relaxed_r1cs,
+        |relaxed_instance, instance, commit_t| {
+            ivc_challenge(vk, relaxed_instance, instance, commit_t)
+        },
+        rng,
+    )
+}
+
+/// fold r1cs into relaxed r1cs with challenge r ← p(U, u, T) squeezed by given transcript
+pub(crate) fn fold_r1cs_with<C: TwistedEdwardsAffine>(
+    pp: &PedersenCommitment<C>,
+    r1cs: R1cs<C>,
+    relaxed_r1cs: RelaxedR1cs<C>,
+    challenge: impl FnOnce(&RelaxedR1csInstance<C>, &R1csInstance<C>, &C) -> C::Scalar,
+    mut rng: impl RngCore,
+) -> (RelaxedR1cs<C>, C) {
+    let (w0, x0) = (r1cs.witness.w.clone(), r1cs.instance.x.clone());
+    let (w1, x1) = (
+        relaxed_r1cs.witness.w.clone(),
+        relaxed_r1cs.instance.x.clone(),
+    );
+    let (u1, u2) = (C::Scalar::one(), relaxed_r1cs.instance.u);
+    let t = compute_cross_term(&relaxed_r1cs.r1cs, w0, x0, u1, w1, x1, u2);
+    let r_t = C::Scalar::random(&mut rng);
+    let commit_t = pp.commit(&t, &r_t);
+    let lc_random = challenge(&relaxed_r1cs.instance, &r1cs.instance, &commit_t);
+    let folded_instance = relaxed_r1cs
+        .instance
+        .fold(r1cs.instance, lc_random, commit_t);
+    let folded_witness = relaxed_r1cs.witness.fold(r1cs.witness, lc_random, t, r_t);
+
+    (
+        RelaxedR1cs {
+            r1cs: relaxed_r1cs.r1cs,
+            instance: folded_instance,
+            witness: folded_witness,
+        },
+        commit_t,
+    )
+}
+
+/// T = AZ1 ◦ BZ2 + AZ2 ◦ BZ1 - u1 · CZ2 - u2 · CZ1
+pub(crate) fn compute_cross_term<C: TwistedEdwardsAffine>(
+    f: &R1csStructure<C>,
+    w0: DenseVectors<C::Scalar>,
+    x0: DenseVectors<C::Scalar>,
+    u1: C::Scalar,
+    w1: DenseVectors<C::Scalar>,
+    x1: DenseVectors<C::Scalar>,
+    u2: C::Scalar,
+) -> DenseVectors<C::Scalar> {
+    let R1csStructure { m, l: _, a, b, c } = f.clone();
+
+    // r1cs and z vectors dot product
+    let az2 = a.prod(m, u2, &x1, &w1);
+    let bz1 = b.prod(m, u1, &x0, &w0);
+    let az1 = a.prod(m, u1, &x0, &w0);
+    let bz2 = b.prod(m, u2, &x1, &w1);
+    let cz2 = c.prod(m, u2, &x1, &w1);
+    let cz1 = c.prod(m, u1, &x0, &w0);
+
+    // dense vectors multiplication a.k.a Hadamard product
+    let az2bz1 = az2 * bz1;
+    let az1bz2 = az1 * bz2;
+
+    // dense vectors and random scalar multiplication
+    let c1cz2 = cz2 * u1;
+    let c2cz1 = cz1 * u2;
+
+    // final addition and subtraction
+    az2bz1 + az1bz2 - c1cz2 - c2cz1
+}
+
+/// Td = Σ AZi ◦ BZj - ui · CZj over i + j = d and i ≠ j for d in 1..2k
+/// where Z0 is relaxed r1cs and terms of i = j vanish except E0 which stays in E
+pub(crate) fn compute_batch_cross_terms<C: TwistedEdwardsAffine>(
+    f: &R1csStructure<C>,
+    r1cs: &[R1cs<C>],
+    relaxed_r1cs: &RelaxedR1cs<C>,
+) -> Vec<DenseVectors<C::Scalar>> {
+    let R1csStructure { m, l: _, a, b, c } = f;
+    let k = r1cs.len();
+
+    // r1cs and z vectors dot product once for each instance
+    let (u0, x0, w0) = (
+        relaxed_r1cs.instance.u,
+        &relaxed_r1cs.instance.x,
+        &relaxed_r1cs.witness.w,
+    );
+    let prods = iter::once((u0, x0, w0))
+        .chain(
+            r1cs.iter()
+                .map(|r1cs| (C::Scalar::one(), &r1cs.instance.x, &r1cs.witness.w)),
+        )
+        .map(|(u, x, w)| {
+            (
+                u,
+                a.prod(*m, u, x, w),
+                b.prod(*m, u, x, w),
+                c.prod(*m, u, x, w),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut t = vec![DenseVectors(vec![C::Scalar::zero(); *m]); (2 * k).saturating_sub(1)];
+    for (i, (ui, azi, _, _)) in prods.iter().enumerate() {
+        for (j, (_, _, bzj, czj)) in prods.iter().enumerate().filter(|(j, _)| *j != i) {
+            let td = &mut t[i + j - 1];
+            (0..*m).for_each(|row| td[row] += azi[row] * bzj[row] - *ui * czj[row]);
         }
     }
+    t
+}
 
-    pub(crate) fn compute_cross_term(
+/// pcd prover for merge circuit M derived from step circuit F
+/// each node folds pairs of two children over adjacent ranges and leaves apply F
+pub struct PcdProver<C: TwistedEdwardsAffine> {
+    pub(crate) prover: Prover<C>,
+}
+
+impl<C: TwistedEdwardsAffine> PcdProver<C> {
+    /// prover for M derived from step circuit F
+    pub fn from_circuit(pp: PedersenCommitment<C>, circuit: &impl StepCircuit<C>) -> Self {
+        Self {
+            prover: Prover::new(pp, merge_r1cs(circuit)),
+        }
+    }
+
+    /// verification key
+    pub fn vk(&self) -> &VerificationKey<C::Scalar> {
+        &self.prover.vk
+    }
+
+    /// prove F^steps(z0) by proving leaf subranges of leaf_size steps on local pool of threads
+    /// workers and merging adjacent proofs in binary tree level by level
+    /// rng is called once for each task
+    pub fn prove<R: RngCore>(
         &self,
-        w0: DenseVectors<C::Scalar>,
-        x0: DenseVectors<C::Scalar>,
-        w1: DenseVectors<C::Scalar>,
-        x1: DenseVectors<C::Scalar>,
-        u2: C::Scalar,
-    ) -> DenseVectors<C::Scalar> {
-        let u1 = C::Scalar::one();
-        let R1csStructure { m, l: _, a, b, c } = self.f.clone();
-
-        // r1cs and z vectors dot product
-        let az2 = a.prod(m, &x1, &w1);
-        let bz1 = b.prod(m, &x0, &w0);
-        let az1 = a.prod(m, &x0, &w0);
-        let bz2 = b.prod(m, &x1, &w1);
-        let cz2 = c.prod(m, &x1, &w1);
-        let cz1 = c.prod(m, &x0, &w0);
-
-        // dense vectors multiplication a.k.a Hadamard product
-        let az2bz1 = az2 * bz1;
-        let az1bz2 = az1 * bz2;
-
-        // dense vectors and random scalar multiplication
-        let c1cz2 = cz2 * u1;
-        let c2cz1 = cz1 * u2;
-
-        // final addition and subtraction
-        az2bz1 + az1bz2 - c1cz2 - c2cz1
+        circuit: &(impl StepCircuit<C> + Sync),
+        z0: &[C::Scalar],
+        steps: usize,
+        leaf_size: usize,
+        threads: usize,
+        rng: impl Fn() -> R + Sync,
+    ) -> PcdProof<C> {
+        assert!(steps > 0 && leaf_size > 0, "at least one step is required");
+
+        // zi of subrange boundaries are computed natively
+        let zs = (0..steps).fold(vec![z0.to_vec()], |mut zs, i| {
+            zs.push(circuit.output(&zs[i]));
+            zs
+        });
+        let leaves = (0..steps)
+            .step_by(leaf_size)
+            .map(|a| (a, steps.min(a + leaf_size)))
+            .collect();
+        let mut proofs = run_pool(threads, leaves, |(a, b)| {
+            let mut rng = rng();
+            let π = self.leaf(circuit, a, &zs[a], &mut rng);
+            (a + 1..b).fold(π, |π, _| self.extend(circuit, π, &mut rng))
+        });
+
+        while proofs.len() > 1 {
+            let mut pairs = vec![];
+            let mut proofs_iter = proofs.into_iter();
+            while let Some(left) = proofs_iter.next() {
+                pairs.push((left, proofs_iter.next()))
+            }
+            proofs = run_pool(threads, pairs, |(left, right)| match right {
+                Some(right) => self.merge(circuit, left, right, rng()),
+                None => left,
+            });
+        }
+        proofs.pop().unwrap()
     }
+
+    /// prove za+1 = F(za) over [a, a + 1)
+    pub fn leaf(
+        &self,
+        circuit: &impl StepCircuit<C>,
+        a: usize,
+        z: &[C::Scalar],
+        rng: impl RngCore,
+    ) -> PcdProof<C> {
+        let left = (MergeChild::base(a, z.to_vec()), None);
+        let right = (MergeChild::base(a, z.to_vec()), None);
+        self.node(circuit, left, right, true, rng)
+    }
+
+    /// extend Π over [a, b) with zb+1 = F(zb) to [a, b + 1)
+    pub fn extend(
+        &self,
+        circuit: &impl StepCircuit<C>,
+        π: PcdProof<C>,
+        mut rng: impl RngCore,
+    ) -> PcdProof<C> {
+        let right = (MergeChild::base(π.range.1, π.z_out.clone()), None);
+        let left = self.fold_child(π, &mut rng);
+        self.node(circuit, left, right, true, rng)
+    }
+
+    /// merge Π over [a, m) and Π over [m, b) into Π over [a, b)
+    pub fn merge(
+        &self,
+        circuit: &impl StepCircuit<C>,
+        left: PcdProof<C>,
+        right: PcdProof<C>,
+        mut rng: impl RngCore,
+    ) -> PcdProof<C> {
+        assert_eq!(left.range.1, right.range.0, "ranges must be adjacent");
+        let left = self.fold_child(left, &mut rng);
+        let right = self.fold_child(right, &mut rng);
+        self.node(circuit, left, right, false, rng)
+    }
+
+    /// fold (U, u) of child into U'
+    fn fold_child(
+        &self,
+        π: PcdProof<C>,
+        rng: impl RngCore,
+    ) -> (MergeChild<C>, Option<RelaxedR1cs<C>>) {
+        let PcdProof {
+            range,
+            z_in,
+            z_out,
+            proof,
+        } = π;
+        let (relaxed_instance, instance) = (proof.upper_pair.0.clone(), proof.lower_pair.0.clone());
+        let (folded_r1cs, commit_t) = self.prover.fold(proof, rng);
+        let child = MergeChild {
+            range,
+            z_in,
+            z_out,
+            relaxed_instance,
+            instance,
+            commit_t,
+        };
+        (child, Some(folded_r1cs))
+    }
+
+    /// fold U' of both children, trace M and output Π for node
+    fn node(
+        &self,
+        circuit: &impl StepCircuit<C>,
+        left: (MergeChild<C>, Option<RelaxedR1cs<C>>),
+        right: (MergeChild<C>, Option<RelaxedR1cs<C>>),
+        step: bool,
+        mut rng: impl RngCore,
+    ) -> PcdProof<C> {
+        let Prover { pp, f, vk, .. } = &self.prover;
+        let ((left, left_r1cs), (right, right_r1cs)) = (left, right);
+        assert_eq!(left.z_out, right.z_in);
+
+        // base children are trivial relaxed r1cs
+        let trivial = |n| RelaxedR1cs {
+            r1cs: f.clone(),
+            instance: RelaxedR1csInstance::init(f.clone()),
+            witness: RelaxedR1csWitness::init(f.clone(), n),
+        };
+        let (folded_r1cs, commit_t) = match (left_r1cs, right_r1cs) {
+            (None, None) => (None, C::ADDITIVE_IDENTITY),
+            (left_r1cs, right_r1cs) => {
+                let n = left_r1cs
+                    .as_ref()
+                    .or(right_r1cs.as_ref())
+                    .map_or(0, |r1cs| r1cs.witness.w.0.len());
+                let (folded_r1cs, commit_t) = fold_relaxed_r1cs_with(
+                    &self.prover.pp,
+                    left_r1cs.unwrap_or_else(|| trivial(n)),
+                    right_r1cs.unwrap_or_else(|| trivial(n)),
+                    |relaxed_instance, instance, commit_t| {
+                        pcd_challenge(vk, relaxed_instance, instance, commit_t)
+                    },
+                    &mut rng,
+                );
+                (Some(folded_r1cs), commit_t)
+            }
+        };
+
+        // trace M for u = (H(vk, a, b, za, zb, U'))
+        let merge_circuit = MergeCircuit {
+            digest: vk.digest,
+            left,
+            right,
+            commit_t,
+            step,
+        };
+        let mut cs = ConstraintSystem::new();
+        let z_out = merge_circuit.synthesize(&mut cs, circuit);
+        let z = cs.assignment();
+        let folded_r1cs = folded_r1cs.unwrap_or_else(|| trivial(z.len() - f.l));
+        debug_assert_eq!(folded_r1cs.instance, merge_circuit.folded_instance());
+        let R1cs {
+            instance, witness, ..
+        } = R1cs::new(f, &z, pp, rng).expect("assignment matches structure");
+
+        PcdProof {
+            range: merge_circuit.range(),
+            z_in: merge_circuit.left.z_in,
+            z_out,
+            proof: IvcProof {
+                upper_pair: (folded_r1cs.instance, folded_r1cs.witness),
+                lower_pair: (instance, witness),
+            },
+        }
+    }
+}
+
+/// non-uniform ivc prover holding F'j for each step circuit Fj
+/// each step runs Fj selected by program counter zi[0] and folds only running instance of
+/// step circuit which output last instance
+pub struct NivcProver<C: TwistedEdwardsAffine> {
+    pub(crate) pp: PedersenCommitment<C>,
+    pub(crate) f: Vec<R1csStructure<C>>,
+    /// witness length of each F'j for trivial running witnesses
+    pub(crate) n: Vec<usize>,
+    pub(crate) vk: VerificationKey<C::Scalar>,
+    pub(crate) i: usize,
+}
+
+impl<C: TwistedEdwardsAffine> NivcProver<C> {
+    /// prover for F'j derived from each step circuit Fj
+    pub fn from_circuits(pp: PedersenCommitment<C>, circuits: &[&dyn StepCircuit<C>]) -> Self {
+        let (f, n): (Vec<_>, _) = (0..circuits.len())
+            .map(|index| nivc_r1cs(circuits, index))
+            .unzip();
+        let vk = VerificationKey::new(&pp, &f.iter().collect::<Vec<_>>());
+        Self { pp, f, n, vk, i: 0 }
+    }
+
+    /// verification key
+    pub fn vk(&self) -> &VerificationKey<C::Scalar> {
+        &self.vk
+    }
+
+    /// number of steps proven so far
+    pub fn steps(&self) -> usize {
+        self.i
+    }
+
+    /// P(pk, (i, z0, zi), ωi, Πi) → (zi+1, Πi+1) running Fj for j = zi[0]
+    /// where Πi is None at base case
+    pub fn recurse(
+        &mut self,
+        circuits: &[&dyn StepCircuit<C>],
+        z0: &[C::Scalar],
+        zi: &[C::Scalar],
+        πi: Option<NivcProof<C>>,
+        mut rng: impl RngCore,
+    ) -> (Vec<C::Scalar>, NivcProof<C>) {
+        let digest = self.vk.digest;
+        let index = program_counter(zi, circuits.len());
+        let (nivc_circuit, upper_pairs) = if self.i == 0 {
+            // base case starts from trivial relaxed r1cs for each circuit
+            let upper_pairs = self
+                .f
+                .iter()
+                .zip(self.n.iter())
+                .map(|(f, n)| {
+                    (
+                        RelaxedR1csInstance::init(f.clone()),
+                        RelaxedR1csWitness::init(f.clone(), *n),
+                    )
+                })
+                .collect();
+            (
+                NivcCircuit::base(digest, index, circuits.len(), z0.to_vec()),
+                upper_pairs,
+            )
+        } else {
+            // fold previous step into running instance of circuit which output it
+            let NivcProof {
+                mut upper_pairs,
+                lower_pair,
+                index: last_index,
+            } = πi.expect("previous proof is required after base case");
+            let relaxed_instances = upper_pairs
+                .iter()
+                .map(|(instance, _)| instance.clone())
+                .collect();
+            let instance = lower_pair.0.clone();
+            let f = &self.f[last_index];
+            let (relaxed_instance, relaxed_witness) = upper_pairs[last_index].clone();
+            let relaxed_r1cs = RelaxedR1cs {
+                r1cs: f.clone(),
+                instance: relaxed_instance,
+                witness: relaxed_witness,
+            };
+            let r1cs = R1cs {
+                r1cs: f.clone(),
+                instance: lower_pair.0,
+                witness: lower_pair.1,
+            };
+            let (folded_r1cs, commit_t) =
+                fold_r1cs(&self.pp, &self.vk, r1cs, relaxed_r1cs, &mut rng);
+            upper_pairs[last_index] = (folded_r1cs.instance, folded_r1cs.witness);
+            let nivc_circuit = NivcCircuit {
+                digest,
+                i: self.i,
+                index,
+                last_index,
+                z0: z0.to_vec(),
+                zi: zi.to_vec(),
+                relaxed_instances,
+                instance,
+                commit_t,
+            };
+            (nivc_circuit, upper_pairs)
+        };
+
+        // trace F'j for ui+1 = (H(vk, i+1, j, z0, zi+1, U⃗i+1))
+        let circuit = circuits[index];
+        let mut cs = ConstraintSystem::new();
+        let zi1 = nivc_circuit.synthesize(&mut cs, circuit);
+        assert_eq!(zi1, circuit.output(zi));
+        let z = cs.assignment();
+        debug_assert!(upper_pairs
+            .iter()
+            .map(|(instance, _)| instance)
+            .eq(nivc_circuit.folded_instances().iter()));
+        let R1cs {
+            instance, witness, ..
+        } = R1cs::new(&self.f[index], &z, &self.pp, rng).expect("assignment matches structure");
+        self.i += 1;
+
+        (
+            zi1,
+            NivcProof {
+                upper_pairs,
+                lower_pair: (instance, witness),
+                index,
+            },
+        )
+    }
+}
+
+/// run f over items on local pool of threads workers and output results in order
+fn run_pool<T: Send, U: Send>(threads: usize, items: Vec<T>, f: impl Fn(T) -> U + Sync) -> Vec<U> {
+    let len = items.len();
+    let items = items
+        .into_iter()
+        .map(|item| Mutex::new(Some(item)))
+        .collect::<Vec<_>>();
+    let results = (0..len).map(|_| Mutex::new(None)).collect::<Vec<_>>();
+    let cursor = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for _ in 0..threads.clamp(1, len.max(1)) {
+            s.spawn(|| loop {
+                let index = cursor.fetch_add(1, Ordering::Relaxed);
+                if index >= len {
+                    break;
+                }
+                let item = items[index].lock().unwrap().take().unwrap();
+                let result = f(item);
+                *results[index].lock().unwrap() = Some(result);
+            });
+        }
+    });
+    results
+        .into_iter()
+        .map(|result| result.into_inner().unwrap().unwrap())
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::Prover;
+    use crate::proof::IvcProof;
     use crate::public_param::PedersenCommitment;
-    use crate::tests::{example_r1cs, example_r1cs_instance, example_relaxed_r1cs_instance};
+    use crate::r1cs::R1csInstance;
+    use crate::relaxed_r1cs::RelaxedR1csInstance;
+    use crate::tests::{
+        example_r1cs, example_r1cs_instance, example_relaxed_r1cs_instance, ExampleCircuit,
+    };
 
-    use jub_jub::JubjubAffine as Curve;
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
     use rand_core::OsRng;
+    use zkstd::common::PrimeField;
 
     #[test]
     fn folding_test() {
         let r1cs = example_r1cs::<Curve>();
-        let n = r1cs.m.next_power_of_two() as u64;
-        let pp = PedersenCommitment::<Curve>::new(n, OsRng);
-        let r1cs_instance = example_r1cs_instance::<Curve>(3);
-        let relaxed_r1cs_instance = example_relaxed_r1cs_instance::<Curve>(4);
+        let pp = PedersenCommitment::from_r1cs(&r1cs, OsRng);
+        let r1cs_instance = example_r1cs_instance::<Curve>(&pp, 3);
+        let relaxed_r1cs_instance = example_relaxed_r1cs_instance::<Curve>(&pp, 4);
+        let prover = Prover::new(pp, r1cs);
+        let (folded_r1cs_instance, _) = prover.prove(r1cs_instance, relaxed_r1cs_instance, OsRng);
+        let (instance, witness) = (
+            &folded_r1cs_instance.instance,
+            &folded_r1cs_instance.witness,
+        );
+
+        assert!(folded_r1cs_instance.is_sat());
+        assert_eq!(
+            instance.commit_w,
+            prover.pp.commit(&witness.w, &witness.r_w)
+        );
+        assert_eq!(
+            instance.commit_e,
+            prover.pp.commit(&witness.e, &witness.r_e)
+        )
+    }
+
+    #[test]
+    fn relaxed_folding_test() {
+        let r1cs = example_r1cs::<Curve>();
+        let pp = PedersenCommitment::from_r1cs(&r1cs, OsRng);
+        let prover = Prover::new(pp, r1cs);
+        // relaxed r1cs with u ≠ 1 and E ≠ 0 on both sides
+        let fold = |i, j| {
+            let r1cs_instance = example_r1cs_instance::<Curve>(&prover.pp, i);
+            let relaxed_r1cs_instance = example_relaxed_r1cs_instance::<Curve>(&prover.pp, j);
+            prover.prove(r1cs_instance, relaxed_r1cs_instance, OsRng).0
+        };
+        let (relaxed_r1cs_instance1, relaxed_r1cs_instance2) = (fold(3, 4), fold(5, 6));
+        let (folded_r1cs_instance, _) =
+            prover.prove_relaxed(relaxed_r1cs_instance1, relaxed_r1cs_instance2, OsRng);
+        let (instance, witness) = (
+            &folded_r1cs_instance.instance,
+            &folded_r1cs_instance.witness,
+        );
+
+        assert!(folded_r1cs_instance.is_sat());
+        assert_eq!(
+            instance.commit_w,
+            prover.pp.commit(&witness.w, &witness.r_w)
+        );
+        assert_eq!(
+            instance.commit_e,
+            prover.pp.commit(&witness.e, &witness.r_e)
+        )
+    }
+
+    #[test]
+    fn batch_folding_test() {
+        let r1cs = example_r1cs::<Curve>();
+        let pp = PedersenCommitment::from_r1cs(&r1cs, OsRng);
         let prover = Prover::new(pp, r1cs);
-        let folded_r1cs_instance = prover.prove(r1cs_instance, relaxed_r1cs_instance);
+        let r1cs_instance = example_r1cs_instance::<Curve>(&prover.pp, 3);
+        let relaxed_r1cs_instance = example_relaxed_r1cs_instance::<Curve>(&prover.pp, 4);
+        let (relaxed_r1cs_instance, _) = prover.prove(r1cs_instance, relaxed_r1cs_instance, OsRng);
+        let r1cs_instances = (5..10)
+            .map(|i| example_r1cs_instance::<Curve>(&prover.pp, i))
+            .collect();
+        let (folded_r1cs_instance, commit_t) =
+            prover.prove_batch(r1cs_instances, relaxed_r1cs_instance, OsRng);
+        let (instance, witness) = (
+            &folded_r1cs_instance.instance,
+            &folded_r1cs_instance.witness,
+        );
 
-        assert!(folded_r1cs_instance.is_sat())
+        assert_eq!(commit_t.len(), 9);
+        assert!(folded_r1cs_instance.is_sat());
+        assert_eq!(
+            instance.commit_w,
+            prover.pp.commit(&witness.w, &witness.r_w)
+        );
+        assert_eq!(
+            instance.commit_e,
+            prover.pp.commit(&witness.e, &witness.r_e)
+        )
+    }
+
+    #[test]
+    fn recurse_test() {
+        let circuit = ExampleCircuit;
+        let pp = PedersenCommitment::<Curve>::from_circuit(&circuit, OsRng);
+        let mut prover = Prover::from_circuit(pp, &circuit);
+        let z0 = vec![Scalar::from(2)];
+        let (mut zi, mut πi) = (z0.clone(), None);
+
+        for i in 0..3 {
+            let (zi1, πi1) = prover.recurse(&circuit, &z0, &zi, πi, OsRng);
+            let IvcProof {
+                upper_pair,
+                lower_pair,
+            } = πi1.clone();
+            let relaxed_r1cs = RelaxedR1csInstance {
+                r1cs: prover.f.clone(),
+                instance: upper_pair.0,
+                witness: upper_pair.1,
+            };
+            let r1cs = R1csInstance {
+                r1cs: prover.f.clone(),
+                instance: lower_pair.0,
+                witness: lower_pair.1,
+            };
+
+            assert_eq!(prover.i, i + 1);
+            assert_eq!(zi1[0], zi[0].square() * zi[0] + zi[0] + Scalar::from(5));
+            assert!(relaxed_r1cs.is_sat());
+            assert!(r1cs.is_sat());
+            (zi, πi) = (zi1, Some(πi1))
+        }
     }
 }
@@ -1,7 +1,21 @@
+mod hash_to_curve;
+mod msm;
+
+pub(crate) use msm::{msm, msm_parallel};
+
+use hash_to_curve::hash_to_curve;
+
+use crate::augmented_circuit::augmented_r1cs;
 use crate::hash::Digest;
 use crate::matrix::DenseVectors;
+use crate::r1cs::R1csStructure;
+use crate::step_circuit::StepCircuit;
 
-use zkstd::common::{CurveAffine, CurveGroup, RngCore};
+use std::thread;
+use zkstd::common::{CurveAffine, RngCore, TwistedEdwardsAffine};
+
+/// vector length from which commitment splits msm windows across threads
+const PARALLEL_THRESHOLD: usize = 1 << 12;
 
 pub(crate) struct Proof<C: CurveAffine> {
     r: C,
@@ -9,37 +23,177 @@ pub(crate) struct Proof<C: CurveAffine> {
     r_u: C::Scalar,
 }
 
+#[derive(Clone, Debug)]
 pub struct PedersenCommitment<C: CurveAffine> {
-    h: C,
-    g: Vec<C>,
+    pub(crate) h: C,
+    pub(crate) g: Vec<C>,
+    /// domain separation label of derived bases which is empty for sampled ones
+    pub(crate) label: Vec<u8>,
 }
 
 impl<C: CurveAffine> PedersenCommitment<C> {
-    pub(crate) fn new(n: u64, mut r: impl RngCore) -> Self {
+    /// sample h and size bases g
+    pub fn new(size: usize, mut r: impl RngCore) -> Self {
         let h = C::Affine::random(&mut r).into();
-        let g = (0..=1 << n)
+        let g = (0..size)
             .map(|_| C::Affine::random(&mut r).into())
             .collect();
-        Self { h, g }
+        Self {
+            h,
+            g,
+            label: vec![],
+        }
     }
 
+    /// smaller key with same h and first size bases of universal key
+    pub fn trim(&self, size: usize) -> Self {
+        assert!(size <= self.g.len(), "trimmed size exceeds number of bases");
+        Self {
+            h: self.h,
+            g: self.g[..size].to_vec(),
+            label: self.label.clone(),
+        }
+    }
+
+    /// h · r + Σ gi · mi by pippenger msm which runs on available threads for long vectors
     pub(crate) fn commit(&self, m: &DenseVectors<C::Scalar>, r: &C::Scalar) -> C {
-        (self.h * r
-            + m.iter()
-                .zip(self.g.iter())
-                .fold(C::Extended::ADDITIVE_IDENTITY, |sum, (v, e)| sum + *e * v))
-        .into()
+        assert!(m.0.len() <= self.g.len(), "message exceeds number of bases");
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        if m.0.len() >= PARALLEL_THRESHOLD && threads > 1 {
+            self.commit_parallel(m, r, threads)
+        } else {
+            (self.h * r + msm(&self.g, &m.0)).into()
+        }
+    }
+
+    /// commit with msm windows split across threads workers
+    pub(crate) fn commit_parallel(
+        &self,
+        m: &DenseVectors<C::Scalar>,
+        r: &C::Scalar,
+        threads: usize,
+    ) -> C {
+        (self.h * r + msm_parallel(&self.g, &m.0, threads)).into()
     }
 
-    pub(crate) fn digest(&self) -> C::Scalar {
+    /// digest H(label, size, h, g) absorbed by verification key
+    pub fn digest(&self) -> C::Scalar {
         let mut hasher = Digest::default();
+        hasher.update(b"pedersen");
+        hasher.update(&(self.label.len() as u64).to_le_bytes());
+        hasher.update(&self.label);
+        hasher.update(&(self.g.len() as u64).to_le_bytes());
+        hasher.update(&self.h.to_raw_bytes());
         self.g
             .iter()
             .for_each(|base| hasher.update(&base.to_raw_bytes()));
         hasher.finalize()
     }
 
-    pub(crate) fn size(&self) -> usize {
+    /// number of bases g
+    pub fn size(&self) -> usize {
         self.g.len()
     }
 }
+
+impl<C: TwistedEdwardsAffine> PedersenCommitment<C> {
+    /// sample key for W of witness length and E of m constraints which share prefix of bases
+    pub fn from_r1cs(r1cs: &R1csStructure<C>, r: impl RngCore) -> Self {
+        Self::new(Self::r1cs_size(r1cs), r)
+    }
+
+    /// derive key for W and E of r1cs by hash to curve on domain separation label
+    pub fn from_r1cs_label(label: &[u8], r1cs: &R1csStructure<C>) -> Self {
+        Self::from_label(label, Self::r1cs_size(r1cs))
+    }
+
+    /// sample key for augmented circuit F' of step circuit F
+    pub fn from_circuit(circuit: &impl StepCircuit<C>, r: impl RngCore) -> Self {
+        Self::from_r1cs(&augmented_r1cs(circuit), r)
+    }
+
+    /// number of bases (pp_W, pp_E) for W and E of r1cs padded to power of two with extra base
+    /// bound by snark openings, where W is padded to cover (u, x) half of z
+    pub fn r1cs_sizes(r1cs: &R1csStructure<C>) -> (usize, usize) {
+        let w = r1cs.witness_len().max(r1cs.l + 1);
+        (w.next_power_of_two() + 1, r1cs.m.next_power_of_two() + 1)
+    }
+
+    /// number of bases of one key covering both pp_W and pp_E
+    pub fn r1cs_size(r1cs: &R1csStructure<C>) -> usize {
+        let (w, e) = Self::r1cs_sizes(r1cs);
+        w.max(e)
+    }
+
+    /// derive h and size bases g by hash to curve on domain separation label
+    /// so anyone can regenerate parameters and check digest
+    pub fn from_label(label: &[u8], size: usize) -> Self {
+        let h = hash_to_curve(label, b"h", 0);
+        let g = (0..size as u64)
+            .map(|i| hash_to_curve(label, b"g", i))
+            .collect();
+        Self {
+            h,
+            g,
+            label: label.to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PedersenCommitment;
+    use crate::matrix::DenseVectors;
+    use crate::tests::example_r1cs;
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use rand_core::OsRng;
+    use zkstd::common::Group;
+
+    #[test]
+    fn trim_test() {
+        let r1cs = example_r1cs::<Curve>();
+        let size = PedersenCommitment::r1cs_size(&r1cs);
+        let pp = PedersenCommitment::<Curve>::from_label(b"test", 65);
+        let trimmed = pp.trim(size);
+
+        let (size_w, size_e) = PedersenCommitment::r1cs_sizes(&r1cs);
+        assert!(size_w > r1cs.witness_len() && size_w < 2 * r1cs.witness_len() + 2);
+        assert!(size_e > r1cs.m() && size_e < 2 * r1cs.m() + 2);
+        assert_eq!(size, size_w.max(size_e));
+        assert_eq!(PedersenCommitment::from_r1cs(&r1cs, OsRng).size(), size);
+        assert_eq!(trimmed.size(), size);
+
+        // label derived key for r1cs is prefix of universal key with same label
+        assert_eq!(
+            PedersenCommitment::from_r1cs_label(b"test", &r1cs).digest(),
+            trimmed.digest()
+        );
+
+        // trimmed key commits to short vectors like universal key
+        let m = DenseVectors((0..size).map(|_| Scalar::random(OsRng)).collect());
+        let r = Scalar::random(OsRng);
+        assert_eq!(trimmed.commit(&m, &r), pp.commit(&m, &r))
+    }
+
+    #[test]
+    fn digest_test() {
+        let pp = PedersenCommitment::<Curve>::from_label(b"test", 9);
+        let blinded = PedersenCommitment {
+            h: pp.g[0],
+            ..pp.clone()
+        };
+
+        // digest is reproducible and binds label, size and h besides g
+        assert_eq!(
+            PedersenCommitment::<Curve>::from_label(b"test", 9).digest(),
+            pp.digest()
+        );
+        assert_ne!(
+            PedersenCommitment::<Curve>::from_label(b"other", 9).digest(),
+            pp.digest()
+        );
+        assert_ne!(pp.trim(4).digest(), pp.digest());
+        assert_ne!(blinded.digest(), pp.digest())
+    }
+}
@@ -0,0 +1,146 @@
+use crate::hash::Digest;
+
+use zkstd::common::{CurveGroup, FftField, Group, PrimeField, Ring, TwistedEdwardsAffine};
+
+/// point of prime order subgroup from blake2b(label, tag, index, counter) by try and increment
+/// y is hashed until (1 - y^2) / (a - d · y^2) is square and x is its root
+/// then cofactor is cleared by 8 which covers twisted edwards cofactors 4 and 8
+pub(crate) fn hash_to_curve<C: TwistedEdwardsAffine>(label: &[u8], tag: &[u8], index: u64) -> C {
+    let one = C::Range::one();
+    (0u64..)
+        .find_map(|counter| {
+            let mut hasher = Digest::default();
+            hasher.update(label);
+            hasher.update(tag);
+            hasher.update(&index.to_le_bytes());
+            hasher.update(&counter.to_le_bytes());
+            let y: C::Range = hasher.finalize();
+            let y2 = y.square();
+            let x2 = (one - y2) * (C::PARAM_A - C::PARAM_D * y2).invert()?;
+            let point = C::from_raw_unchecked(sqrt(x2)?, y);
+            let point = point.double().double().double();
+            (!point.is_identity()).then_some(point.into())
+        })
+        .unwrap()
+}
+
+/// square root by tonelli shanks where p - 1 = q · 2^s
+pub(crate) fn sqrt<F: PrimeField + FftField>(a: F) -> Option<F> {
+    if a.is_zero() {
+        return Some(a);
+    }
+    let minus_one = F::zero() - F::one();
+    let p_minus_one = minus_one.to_raw_bytes();
+    if pow(a, &shr(&p_minus_one, 1)) != F::one() {
+        return None;
+    }
+    let s = (0..256).find(|i| (p_minus_one[i / 8] >> (i % 8)) & 1 == 1)?;
+    let q = shr(&p_minus_one, s);
+    let z = (2u64..)
+        .map(F::from)
+        .find(|z| pow(*z, &shr(&p_minus_one, 1)) == minus_one)?;
+
+    let (mut m, mut c) = (s, pow(z, &q));
+    let mut t = pow(a, &q);
+    let mut r = pow(a, &shr(&add_one(&q), 1));
+    while t != F::one() {
+        // least i with t^(2^i) = 1
+        let i = (1..m).find(|i| (0..*i).fold(t, |t, _| t.square()) == F::one())?;
+        let b = (0..m - i - 1).fold(c, |b, _| b.square());
+        m = i;
+        c = b.square();
+        t *= c;
+        r *= b;
+    }
+    Some(r)
+}
+
+/// base^exp for little endian exponent bytes
+fn pow<F: PrimeField>(base: F, exp: &[u8; 32]) -> F {
+    exp.iter().rev().fold(F::one(), |acc, byte| {
+        (0..8).rev().fold(acc, |acc, i| {
+            let acc = acc.square();
+            if (byte >> i) & 1 == 1 {
+                acc * base
+            } else {
+                acc
+            }
+        })
+    })
+}
+
+/// little endian bytes shifted right by bits
+fn shr(bytes: &[u8; 32], bits: usize) -> [u8; 32] {
+    let mut shifted = [0u8; 32];
+    (0..256 - bits).for_each(|i| {
+        let bit = (bytes[(i + bits) / 8] >> ((i + bits) % 8)) & 1;
+        shifted[i / 8] |= bit << (i % 8);
+    });
+    shifted
+}
+
+/// little endian bytes plus one
+fn add_one(bytes: &[u8; 32]) -> [u8; 32] {
+    let mut sum = *bytes;
+    for byte in sum.iter_mut() {
+        let (value, carry) = byte.overflowing_add(1);
+        *byte = value;
+        if !carry {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_to_curve, sqrt};
+    use crate::public_param::PedersenCommitment;
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use zkstd::common::{CurveGroup, PrimeField, TwistedEdwardsCurve};
+
+    type Fp = <Curve as CurveGroup>::Range;
+
+    #[test]
+    fn sqrt_test() {
+        for i in 1..100 {
+            let a = Fp::from(i);
+            assert_eq!(sqrt(a.square()).map(|r| r.square()), Some(a.square()));
+            if let Some(r) = sqrt(a) {
+                assert_eq!(r.square(), a)
+            }
+        }
+    }
+
+    #[test]
+    fn hash_to_curve_test() {
+        let minus_one = Scalar::zero() - Scalar::one();
+        for i in 0..10 {
+            let point = hash_to_curve::<Curve>(b"test", b"g", i);
+            let (x, y) = (point.get_x(), point.get_y());
+            let (x2, y2) = (x.square(), y.square());
+
+            // on curve, in prime order subgroup and deterministic
+            assert_eq!(
+                Curve::PARAM_A * x2 + y2,
+                Fp::one() + Curve::PARAM_D * x2 * y2
+            );
+            assert_eq!(Curve::from(point * minus_one), -point);
+            assert_eq!(point, hash_to_curve(b"test", b"g", i));
+            assert_ne!(point, hash_to_curve(b"other", b"g", i));
+        }
+
+        // parameters are reproducible from label
+        let pp = PedersenCommitment::<Curve>::from_label(b"test", 9);
+        assert_eq!(pp.size(), 9);
+        assert_eq!(
+            pp.digest(),
+            PedersenCommitment::<Curve>::from_label(b"test", 9).digest()
+        );
+        assert_ne!(
+            pp.digest(),
+            PedersenCommitment::<Curve>::from_label(b"other", 9).digest()
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::thread;
+use zkstd::common::{CurveAffine, CurveExtended, CurveGroup, FftField, PrimeField, Ring};
+
+/// number of bits read from scalar
+const SCALAR_BITS: usize = 256;
+
+/// Σ si · Pi by pippenger bucket method with c bit windows
+/// zero scalars are skipped and one scalars are added directly
+pub(crate) fn msm<C: CurveAffine>(points: &[C], scalars: &[C::Scalar]) -> C::Extended {
+    let (ones, pairs) = split(points, scalars);
+    let c = window_size(pairs.len());
+    let windows = (0..SCALAR_BITS)
+        .step_by(c)
+        .map(|offset| window_sum(&pairs, offset, c))
+        .collect::<Vec<_>>();
+    combine(windows, c) + ones
+}
+
+/// msm whose windows are split into buckets on threads workers
+pub(crate) fn msm_parallel<C: CurveAffine>(
+    points: &[C],
+    scalars: &[C::Scalar],
+    threads: usize,
+) -> C::Extended {
+    let (ones, pairs) = split(points, scalars);
+    let c = window_size(pairs.len());
+    let offsets = (0..SCALAR_BITS).step_by(c).collect::<Vec<_>>();
+    let chunk = offsets.len() / threads.max(1) + 1;
+    let windows = thread::scope(|s| {
+        // spawn every chunk before joining any so windows run concurrently
+        let mut handles = Vec::with_capacity(threads);
+        for offsets in offsets.chunks(chunk) {
+            let pairs = &pairs;
+            handles.push(s.spawn(move || {
+                offsets
+                    .iter()
+                    .map(|offset| window_sum(pairs, *offset, c))
+                    .collect::<Vec<_>>()
+            }));
+        }
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    combine(windows, c) + ones
+}
+
+/// sum of points with one scalar and pairs of points and little endian scalar bytes for others
+fn split<C: CurveAffine>(points: &[C], scalars: &[C::Scalar]) -> (C::Extended, Vec<(C, [u8; 32])>) {
+    let mut ones = C::Extended::ADDITIVE_IDENTITY;
+    let pairs = points
+        .iter()
+        .zip(scalars.iter())
+        .filter_map(|(point, scalar)| {
+            if scalar.is_zero() {
+                None
+            } else if *scalar == C::Scalar::one() {
+                ones += point.to_extended();
+                None
+            } else {
+                Some((*point, scalar.to_raw_bytes()))
+            }
+        })
+        .collect();
+    (ones, pairs)
+}
+
+/// window size c ≈ ln(n) + 2
+fn window_size(n: usize) -> usize {
+    if n < 32 {
+        3
+    } else {
+        let log2 = (usize::BITS - n.leading_zeros() - 1) as usize;
+        (log2 * 69 / 100 + 2).min(16)
+    }
+}
+
+/// Σ k · Bk over buckets Bk of window at offset by running sum
+fn window_sum<C: CurveAffine>(pairs: &[(C, [u8; 32])], offset: usize, c: usize) -> C::Extended {
+    let mut buckets = vec![C::Extended::ADDITIVE_IDENTITY; (1 << c) - 1];
+    pairs.iter().for_each(|(point, bytes)| {
+        let digit = (0..c).fold(0, |digit, i| {
+            let bit = offset + i;
+            match bytes.get(bit / 8) {
+                Some(byte) => digit | (((byte >> (bit % 8)) & 1) as usize) << i,
+                None => digit,
+            }
+        });
+        if digit != 0 {
+            buckets[digit - 1] += point.to_extended();
+        }
+    });
+    let mut running = C::Extended::ADDITIVE_IDENTITY;
+    buckets.into_iter().rev().fold(running, |sum, bucket| {
+        running += bucket;
+        sum + running
+    })
+}
+
+/// Σ 2^(c · w) · Sw by doubling c times from highest window
+fn combine<E: CurveExtended>(windows: Vec<E>, c: usize) -> E {
+    windows
+        .into_iter()
+        .rev()
+        .fold(E::ADDITIVE_IDENTITY, |sum, window| {
+            (0..c).fold(sum, |sum, _| sum.double()) + window
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{msm, msm_parallel};
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve, JubjubExtended as Extended};
+    use rand_core::OsRng;
+    use zkstd::common::{CurveGroup, Group};
+
+    #[test]
+    fn msm_test() {
+        for n in [0, 1, 5, 33, 300] {
+            let points = (0..n)
+                .map(|_| Curve::random(OsRng).into())
+                .collect::<Vec<Curve>>();
+            let scalars = (0..n)
+                .map(|i| match i % 4 {
+                    0 => Scalar::zero(),
+                    1 => Scalar::one(),
+                    _ => Scalar::random(OsRng),
+                })
+                .collect::<Vec<_>>();
+            let naive = points
+                .iter()
+                .zip(scalars.iter())
+                .fold(Extended::ADDITIVE_IDENTITY, |sum, (p, s)| sum + *p * s);
+
+            assert_eq!(msm(&points, &scalars), naive);
+            assert_eq!(msm_parallel(&points, &scalars, 4), naive);
+        }
+    }
+}
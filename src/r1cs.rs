@@ -2,37 +2,63 @@ mod blueprint;
 mod instance;
 mod witness;
 
-pub(crate) use blueprint::R1csStructure;
-pub(crate) use instance::Instance;
-pub(crate) use witness::Witness;
+pub use blueprint::R1csStructure;
+pub use instance::Instance;
+pub use witness::Witness;
 
+use crate::error::Error;
 use crate::matrix::Element;
+use crate::public_param::PedersenCommitment;
 use crate::relaxed_r1cs::RelaxedR1csInstance;
 use crate::wire::Wire;
 
-use zkstd::common::{Group, TwistedEdwardsAffine};
+use core::fmt;
+use zkstd::common::{Group, PrimeField, RngCore, TwistedEdwardsAffine};
 
+/// r1cs structure with committed instance and witness
 #[derive(Debug, Default)]
 pub struct R1csInstance<C: TwistedEdwardsAffine> {
     pub(crate) r1cs: R1csStructure<C>,
-    pub(crate) instance: Instance<C::Scalar>,
+    pub(crate) instance: Instance<C>,
     pub(crate) witness: Witness<C>,
 }
 
 impl<C: TwistedEdwardsAffine> R1csInstance<C> {
-    pub(crate) fn new(r1cs: &R1csStructure<C>, witness: &Vec<C::Scalar>) -> Self {
-        let (instance, witness) = r1cs.instance_and_witness(witness);
+    /// commit witness of assignment z = (x, W) for structure
+    /// z must hold l instances and witness length of structure which fits bases of pp
+    pub fn new(
+        r1cs: &R1csStructure<C>,
+        witness: &[C::Scalar],
+        pp: &PedersenCommitment<C>,
+        rng: impl RngCore,
+    ) -> Result<Self, Error> {
+        let witness_len = r1cs.witness_len();
+        if witness.len() != r1cs.l + witness_len || witness_len > pp.size() {
+            return Err(Error::InvalidLength);
+        }
+        let (instance, witness) = r1cs.instance_and_witness(witness, pp, rng);
         let r1cs = r1cs.clone();
-        Self {
+        Ok(Self {
             r1cs,
             instance,
             witness,
-        }
+        })
+    }
+
+    /// committed instance (W̄, x)
+    pub fn instance(&self) -> &Instance<C> {
+        &self.instance
+    }
+
+    /// witness (W, x, 1)
+    pub fn witness(&self) -> &Witness<C> {
+        &self.witness
     }
 
-    pub(crate) fn relax(&self) -> RelaxedR1csInstance<C> {
+    /// relax with u = 1 and E = 0
+    pub fn relax(&self) -> RelaxedR1csInstance<C> {
         let r1cs = self.r1cs.clone();
-        let (witness, instance) = self.witness.relax(self.r1cs.m);
+        let (witness, instance) = self.witness.relax(self.instance.commit_w, self.r1cs.m);
         RelaxedR1csInstance {
             r1cs,
             instance,
@@ -41,7 +67,7 @@ impl<C: TwistedEdwardsAffine> R1csInstance<C> {
     }
 
     ///  check (A · Z) ◦ (B · Z) = C · Z
-    pub(crate) fn is_sat(&self) -> bool {
+    pub fn is_sat(&self) -> bool {
         let R1csStructure { m, l: _, a, b, c } = self.r1cs.clone();
         (0..m).all(|i| {
             let a_prod = self.dot_product(&a[i]);
@@ -51,8 +77,31 @@ impl<C: TwistedEdwardsAffine> R1csInstance<C> {
         })
     }
 
+    /// every constraint whose A · Z ◦ B · Z differs from C · Z with evaluated rows
+    pub fn unsatisfied(&self) -> Vec<UnsatisfiedConstraint<C::Scalar>> {
+        let R1csStructure { m, l: _, a, b, c } = &self.r1cs;
+        (0..*m)
+            .filter_map(|i| {
+                let (a, b, c) = (
+                    self.dot_product(&a[i]),
+                    self.dot_product(&b[i]),
+                    self.dot_product(&c[i]),
+                );
+                (a * b != c).then_some(UnsatisfiedConstraint {
+                    index: i,
+                    a,
+                    b,
+                    c,
+                    namespace: None,
+                    label: None,
+                    wire_labels: vec![],
+                })
+            })
+            .collect()
+    }
+
     // dot product for each gate
-    fn dot_product(&self, elements: &Vec<Element<C::Scalar>>) -> C::Scalar {
+    fn dot_product(&self, elements: &[Element<C::Scalar>]) -> C::Scalar {
         elements.iter().fold(C::Scalar::zero(), |sum, element| {
             let (wire, value) = (element.0, element.1);
             let coeff = match wire {
@@ -65,20 +114,178 @@ impl<C: TwistedEdwardsAffine> R1csInstance<C> {
     }
 }
 
+/// failing constraint with A · Z, B · Z and C · Z at its row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint<F: PrimeField> {
+    pub(crate) index: usize,
+    pub(crate) a: F,
+    pub(crate) b: F,
+    pub(crate) c: F,
+    pub(crate) namespace: Option<String>,
+    pub(crate) label: Option<String>,
+    pub(crate) wire_labels: Vec<(Wire, String)>,
+}
+
+impl<F: PrimeField> UnsatisfiedConstraint<F> {
+    /// row of constraint
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// evaluated A · Z
+    pub fn a(&self) -> F {
+        self.a
+    }
+
+    /// evaluated B · Z
+    pub fn b(&self) -> F {
+        self.b
+    }
+
+    /// evaluated C · Z
+    pub fn c(&self) -> F {
+        self.c
+    }
+
+    /// namespace constraint was created in
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// label given to constraint
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// labeled wires in rows of constraint with their labels
+    pub fn wire_labels(&self) -> &[(Wire, String)] {
+        &self.wire_labels
+    }
+}
+
+impl<F: PrimeField> fmt::Display for UnsatisfiedConstraint<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "constraint {}", self.index)?;
+        if let Some(label) = &self.label {
+            write!(f, " ({label})")?;
+        }
+        if let Some(namespace) = &self.namespace {
+            write!(f, " in {namespace}")?;
+        }
+        write!(
+            f,
+            " unsatisfied: A · z = {:?}, B · z = {:?}, C · z = {:?}",
+            self.a, self.b, self.c
+        )?;
+        if !self.wire_labels.is_empty() {
+            let labels = self
+                .wire_labels
+                .iter()
+                .map(|(_, label)| label.as_str())
+                .collect::<Vec<_>>();
+            write!(f, " over wires {}", labels.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{R1csInstance, R1csStructure};
+    use crate::error::Error;
+    use crate::matrix::Element;
+    use crate::public_param::PedersenCommitment;
     use crate::tests::{example_r1cs, example_r1cs_witness};
+    use crate::wire::Wire;
 
-    use jub_jub::JubjubAffine as Curve;
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use rand_core::OsRng;
 
     #[test]
     fn r1cs_instance_test() {
         let r1cs: R1csStructure<Curve> = example_r1cs();
+        let pp = PedersenCommitment::from_r1cs(&r1cs, OsRng);
         for i in 0..100 {
             let z = example_r1cs_witness(i);
-            let r1cs_instance = R1csInstance::new(&r1cs, &z);
-            assert!(r1cs_instance.is_sat())
+            let r1cs_instance = R1csInstance::new(&r1cs, &z, &pp, OsRng).unwrap();
+            let (w, r_w) = (&r1cs_instance.witness.w, &r1cs_instance.witness.r_w);
+            assert!(r1cs_instance.is_sat());
+            assert_eq!(r1cs_instance.instance.commit_w, pp.commit(w, r_w))
         }
+
+        // assignment must cover instance and witness of structure exactly
+        let z = example_r1cs_witness(3);
+        let short_z = z[..r1cs.l].to_vec();
+        let long_z = [z.clone(), vec![Scalar::one()]].concat();
+        assert_eq!(
+            R1csInstance::new(&r1cs, &short_z, &pp, OsRng).err(),
+            Some(Error::InvalidLength)
+        );
+        assert_eq!(
+            R1csInstance::new(&r1cs, &long_z, &pp, OsRng).err(),
+            Some(Error::InvalidLength)
+        );
+        assert_eq!(
+            R1csInstance::new(&r1cs, &z, &pp.trim(1), OsRng).err(),
+            Some(Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn unsatisfied_test() {
+        let r1cs: R1csStructure<Curve> = example_r1cs();
+        let pp = PedersenCommitment::from_r1cs(&r1cs, OsRng);
+        let z = example_r1cs_witness(3);
+        assert!(R1csInstance::new(&r1cs, &z, &pp, OsRng)
+            .unwrap()
+            .unsatisfied()
+            .is_empty());
+
+        // rows x^3 + x = sym and sym + 5 = y break with wrong sym
+        let mut invalid_z = z;
+        invalid_z[4] += Scalar::one();
+        let unsatisfied = R1csInstance::new(&r1cs, &invalid_z, &pp, OsRng)
+            .unwrap()
+            .unsatisfied();
+        assert_eq!(
+            unsatisfied.iter().map(|u| u.index()).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert!(unsatisfied
+            .iter()
+            .all(|u| u.a() * u.b() != u.c() && u.namespace().is_none()));
+    }
+
+    #[test]
+    fn digest_test() {
+        let r1cs: R1csStructure<Curve> = example_r1cs();
+        let digest = r1cs.digest();
+        assert_eq!(r1cs.digest(), digest);
+
+        // empty row kept for next constraint isn't part of shape
+        let mut padded = r1cs.clone();
+        padded.a.0.push(vec![]);
+        assert_eq!(padded.digest(), digest);
+
+        // reordered, split and zero entries denote same matrix
+        let mut split = r1cs.clone();
+        let Element(wire, coeff) = split.a.0[0][0].clone();
+        split.a.0[0][0].1 = coeff - Scalar::one();
+        split.a.0[0].push(Element(Wire::One, Scalar::zero()));
+        split.a.0[0].push(Element(wire, Scalar::one()));
+        split.a.0[0].reverse();
+        assert_eq!(split.digest(), digest);
+
+        let mut coeff = r1cs.clone();
+        coeff.a.0[0][0].1 += Scalar::one();
+        assert_ne!(coeff.digest(), digest);
+
+        let mut kind = r1cs.clone();
+        kind.a.0[0][0].0 = Wire::One;
+        assert_ne!(kind.digest(), digest);
+
+        let mut dimension = r1cs;
+        dimension.l += 1;
+        assert_ne!(dimension.digest(), digest)
     }
 }
@@ -1,6 +1,12 @@
+use crate::augmented_circuit::augmented_r1cs;
+use crate::hash::Digest;
 use crate::matrix::{DenseVectors, Element, SparseMatrix};
+use crate::public_param::PedersenCommitment;
+use crate::step_circuit::StepCircuit;
+use crate::wire::Wire;
 
-use zkstd::common::{Ring, TwistedEdwardsAffine};
+use std::collections::BTreeMap;
+use zkstd::common::{FftField, Group, PrimeField, Ring, RngCore, TwistedEdwardsAffine};
 
 pub(crate) use super::instance::Instance;
 pub(crate) use super::witness::Witness;
@@ -33,6 +39,54 @@ impl<C: TwistedEdwardsAffine> Default for R1csStructure<C> {
 }
 
 impl<C: TwistedEdwardsAffine> R1csStructure<C> {
+    /// structure of augmented circuit F' for step circuit F
+    pub fn from_circuit(circuit: &impl StepCircuit<C>) -> Self {
+        augmented_r1cs(circuit)
+    }
+
+    /// number of constraints
+    pub fn m(&self) -> usize {
+        self.m
+    }
+
+    /// instance length
+    pub fn l(&self) -> usize {
+        self.l
+    }
+
+    /// canonical digest over dimensions and sparse entries (row, wire kind, index, coeff)
+    /// of first m rows of A, B and C where each row is sorted by wire with duplicate wires
+    /// merged and zero coefficients dropped, so equal matrices have same digest
+    pub fn digest(&self) -> C::Scalar {
+        let mut hasher = Digest::default();
+        hasher.update(b"r1cs");
+        hasher.update(&(self.m as u64).to_le_bytes());
+        hasher.update(&(self.l as u64).to_le_bytes());
+        [&self.a, &self.b, &self.c].iter().for_each(|matrix| {
+            matrix.0.iter().take(self.m).for_each(|row| {
+                let row = canonical_row(row);
+                hasher.update(&(row.len() as u64).to_le_bytes());
+                row.iter().for_each(|((kind, index), coeff)| {
+                    hasher.update(&[*kind]);
+                    hasher.update(&(*index as u64).to_le_bytes());
+                    hasher.update(&coeff.to_raw_bytes());
+                })
+            })
+        });
+        hasher.finalize()
+    }
+
+    /// witness length as largest witness wire used by constraints
+    pub fn witness_len(&self) -> usize {
+        [&self.a, &self.b, &self.c]
+            .iter()
+            .flat_map(|matrix| matrix.0.iter().flatten())
+            .fold(0, |len, element| match element.0 {
+                Wire::Witness(i) => len.max(i + 1),
+                _ => len,
+            })
+    }
+
     pub(crate) fn append(
         &mut self,
         a: impl Into<Element<C::Scalar>>,
@@ -44,8 +98,15 @@ impl<C: TwistedEdwardsAffine> R1csStructure<C> {
         self.c[self.m].push(c.into());
     }
 
-    pub(crate) fn append_a(&mut self, a: impl Into<Element<C::Scalar>>) {
-        self.a[self.m].push(a.into())
+    pub(crate) fn extend(
+        &mut self,
+        a: Vec<Element<C::Scalar>>,
+        b: Vec<Element<C::Scalar>>,
+        c: Vec<Element<C::Scalar>>,
+    ) {
+        self.a[self.m].extend(a);
+        self.b[self.m].extend(b);
+        self.c[self.m].extend(c);
     }
 
     pub(crate) fn increment(&mut self) {
@@ -55,8 +116,13 @@ impl<C: TwistedEdwardsAffine> R1csStructure<C> {
         self.m += 1
     }
 
-    pub(crate) fn instantiate(&self, z: &Vec<C::Scalar>) -> R1csInstance<C> {
-        let (instance, witness) = self.instance_and_witness(z);
+    pub(crate) fn instantiate(
+        &self,
+        z: &[C::Scalar],
+        pp: &PedersenCommitment<C>,
+        rng: impl RngCore,
+    ) -> R1csInstance<C> {
+        let (instance, witness) = self.instance_and_witness(z, pp, rng);
         R1csInstance {
             r1cs: self.clone(),
             instance,
@@ -66,11 +132,38 @@ impl<C: TwistedEdwardsAffine> R1csStructure<C> {
 
     pub(crate) fn instance_and_witness(
         &self,
-        witnesses: &Vec<C::Scalar>,
-    ) -> (Instance<C::Scalar>, Witness<C>) {
+        witnesses: &[C::Scalar],
+        pp: &PedersenCommitment<C>,
+        rng: impl RngCore,
+    ) -> (Instance<C>, Witness<C>) {
         let w = DenseVectors(witnesses[self.l..].to_vec());
         let x = DenseVectors(witnesses[..self.l].to_vec());
         let one = C::Scalar::one();
-        (Instance { x: x.clone() }, Witness { w, x, one })
+        let r_w = C::Scalar::zero();
+        let mut witness = Witness { w, x, one, r_w };
+        let commit_w = witness.commit(pp, rng);
+        let instance = Instance {
+            commit_w,
+            x: witness.x.clone(),
+        };
+        (instance, witness)
     }
 }
+
+/// row entries keyed by (wire kind, index) in sorted order with coefficients of same wire
+/// summed and zero coefficients dropped
+fn canonical_row<F: PrimeField>(row: &[Element<F>]) -> Vec<((u8, usize), F)> {
+    let mut entries = BTreeMap::new();
+    row.iter().for_each(|Element(wire, coeff)| {
+        let key = match wire {
+            Wire::Witness(i) => (0u8, *i),
+            Wire::Instance(i) => (1, *i),
+            Wire::One => (2, 0),
+        };
+        *entries.entry(key).or_insert_with(F::zero) += *coeff;
+    });
+    entries
+        .into_iter()
+        .filter(|(_, coeff)| *coeff != F::zero())
+        .collect()
+}
@@ -1,10 +1,49 @@
+use crate::hash::Poseidon;
 use crate::matrix::DenseVectors;
+use crate::transcript::ChallengeTranscript;
 
-use zkstd::common::PrimeField;
+use zkstd::common::TwistedEdwardsAffine;
 
-/// instance for r1cs x
-#[derive(Debug, Default)]
-pub struct Instance<F: PrimeField> {
+/// instance for r1cs (W, x)
+#[derive(Clone, Debug)]
+pub struct Instance<C: TwistedEdwardsAffine> {
+    /// commitment for witness vectors
+    pub(crate) commit_w: C,
     /// public inputs and outputs
-    pub(crate) x: DenseVectors<F>,
+    pub(crate) x: DenseVectors<C::Scalar>,
+}
+
+impl<C: TwistedEdwardsAffine> Default for Instance<C> {
+    fn default() -> Self {
+        Self {
+            commit_w: C::ADDITIVE_IDENTITY,
+            x: DenseVectors(vec![]),
+        }
+    }
+}
+
+impl<C: TwistedEdwardsAffine> Instance<C> {
+    /// commitment for witness vectors
+    pub fn commit_w(&self) -> C {
+        self.commit_w
+    }
+
+    /// public inputs and outputs
+    pub fn x(&self) -> &[C::Scalar] {
+        &self.x.0
+    }
+
+    /// absorb (commit_W, x) into sponge
+    pub(crate) fn absorb_sponge(&self, sponge: &mut Poseidon<C::Scalar>) {
+        sponge.absorb_point(&self.commit_w);
+        self.x.iter().for_each(|x| sponge.absorb(x));
+    }
+
+    /// absorb (commit_W, x) into transcript
+    pub(crate) fn absorb<T: ChallengeTranscript<C>>(&self, transcript: &mut T) {
+        transcript.append_point(b"commit_w", &self.commit_w);
+        self.x
+            .iter()
+            .for_each(|x| transcript.append_scalar(b"x", &x));
+    }
 }
@@ -1,7 +1,8 @@
 use crate::matrix::DenseVectors;
+use crate::public_param::PedersenCommitment;
 use crate::relaxed_r1cs::{Instance as RelaxedR1csInstance, Witness as RelaxedR1csWitness};
 
-use zkstd::common::{Group, Ring, TwistedEdwardsAffine};
+use zkstd::common::{Group, Ring, RngCore, TwistedEdwardsAffine};
 
 /// witness for r1cs (W, x, 1)
 #[derive(Clone, Debug)]
@@ -12,6 +13,8 @@ pub struct Witness<C: TwistedEdwardsAffine> {
     pub(crate) x: DenseVectors<C::Scalar>,
     /// first public input element one
     pub(crate) one: C::Scalar,
+    /// randomness for witness commitment
+    pub(crate) r_w: C::Scalar,
 }
 
 impl<C: TwistedEdwardsAffine> Default for Witness<C> {
@@ -20,11 +23,22 @@ impl<C: TwistedEdwardsAffine> Default for Witness<C> {
             w: DenseVectors(vec![]),
             x: DenseVectors(vec![]),
             one: C::Scalar::one(),
+            r_w: C::Scalar::zero(),
         }
     }
 }
 
 impl<C: TwistedEdwardsAffine> Witness<C> {
+    /// intermediate value and private inputs
+    pub fn w(&self) -> &[C::Scalar] {
+        &self.w.0
+    }
+
+    /// public inputs and outputs
+    pub fn x(&self) -> &[C::Scalar] {
+        &self.x.0
+    }
+
     pub(crate) fn get(&self) -> (DenseVectors<C::Scalar>, DenseVectors<C::Scalar>) {
         (self.x.clone(), self.w.clone())
     }
@@ -45,15 +59,27 @@ impl<C: TwistedEdwardsAffine> Witness<C> {
         self.w.0.push(witness)
     }
 
-    pub(crate) fn relax(&self, m: usize) -> (RelaxedR1csWitness<C>, RelaxedR1csInstance<C>) {
-        let Self { w, x, one: _ } = self;
+    /// sample randomness r_W and output Com(pp_W, W, r_W)
+    pub(crate) fn commit(&mut self, pp: &PedersenCommitment<C>, mut rng: impl RngCore) -> C {
+        self.r_w = C::Scalar::random(&mut rng);
+        pp.commit(&self.w, &self.r_w)
+    }
+
+    pub(crate) fn relax(
+        &self,
+        commit_w: C,
+        m: usize,
+    ) -> (RelaxedR1csWitness<C>, RelaxedR1csInstance<C>) {
+        let Self { w, x, one: _, r_w } = self;
         (
             RelaxedR1csWitness {
                 w: w.clone(),
                 e: DenseVectors(vec![C::Scalar::zero(); m]),
+                r_w: *r_w,
+                r_e: C::Scalar::zero(),
             },
             RelaxedR1csInstance {
-                commit_w: C::ADDITIVE_IDENTITY,
+                commit_w,
                 commit_e: C::ADDITIVE_IDENTITY,
                 u: C::Scalar::one(),
                 x: x.clone(),
@@ -2,31 +2,39 @@ mod blueprint;
 mod instance;
 mod witness;
 
-pub(crate) use instance::Instance;
-pub(crate) use witness::Witness;
+pub use instance::Instance;
+pub use witness::Witness;
 
+use crate::matrix::Element;
 use crate::r1cs::R1csStructure;
-use zkstd::common::TwistedEdwardsAffine;
+use crate::wire::Wire;
 
-pub(crate) struct RelaxedR1csInstance<C: TwistedEdwardsAffine> {
+use zkstd::common::{Group, TwistedEdwardsAffine};
+
+/// relaxed r1cs structure with committed relaxed instance and witness
+#[derive(Clone, Debug)]
+pub struct RelaxedR1csInstance<C: TwistedEdwardsAffine> {
     pub(crate) r1cs: R1csStructure<C>,
     pub(crate) instance: Instance<C>,
     pub(crate) witness: Witness<C>,
 }
 
-#[cfg(test)]
-use crate::matrix::Element;
-#[cfg(test)]
-use crate::wire::Wire;
-#[cfg(test)]
-use zkstd::common::Group;
-#[cfg(test)]
 impl<C: TwistedEdwardsAffine> RelaxedR1csInstance<C> {
+    /// committed relaxed instance (Ē, u, W̄, x)
+    pub fn instance(&self) -> &Instance<C> {
+        &self.instance
+    }
+
+    /// relaxed witness (E, rE, W, rW)
+    pub fn witness(&self) -> &Witness<C> {
+        &self.witness
+    }
+
     ///  check (A · Z) ◦ (B · Z) = u · (C · Z) + E
-    pub(crate) fn is_sat(&self) -> bool {
+    pub fn is_sat(&self) -> bool {
         let R1csStructure { m, l: _, a, b, c } = self.r1cs.clone();
         let e = self.witness.e.clone();
-        let u = self.instance.u.clone();
+        let u = self.instance.u;
         (0..m).all(|i| {
             let a_prod = self.dot_product(&a[i]);
             let b_prod = self.dot_product(&b[i]);
@@ -38,7 +46,7 @@ impl<C: TwistedEdwardsAffine> RelaxedR1csInstance<C> {
     }
 
     // dot product for each gate
-    fn dot_product(&self, elements: &Vec<Element<C::Scalar>>) -> C::Scalar {
+    fn dot_product(&self, elements: &[Element<C::Scalar>]) -> C::Scalar {
         elements.iter().fold(C::Scalar::zero(), |sum, element| {
             let (wire, value) = element.get();
             let coeff = match wire {
@@ -53,14 +61,17 @@ impl<C: TwistedEdwardsAffine> RelaxedR1csInstance<C> {
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::example_relaxed_r1cs_instance;
+    use crate::public_param::PedersenCommitment;
+    use crate::tests::{example_r1cs, example_relaxed_r1cs_instance};
 
     use jub_jub::JubjubAffine as Curve;
+    use rand_core::OsRng;
 
     #[test]
     fn relaxed_r1cs_instance_test() {
+        let pp = PedersenCommitment::from_r1cs(&example_r1cs::<Curve>(), OsRng);
         for i in 0..100 {
-            let relaxed_r1cs_instance = example_relaxed_r1cs_instance::<Curve>(i);
+            let relaxed_r1cs_instance = example_relaxed_r1cs_instance::<Curve>(&pp, i);
             assert!(relaxed_r1cs_instance.is_sat())
         }
     }
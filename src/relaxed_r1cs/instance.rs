@@ -1,10 +1,13 @@
+use crate::hash::Poseidon;
 use crate::matrix::DenseVectors;
+use crate::nifs::powers;
 use crate::r1cs::{Instance as R1csInstance, R1csStructure};
+use crate::transcript::ChallengeTranscript;
 
-use zkstd::common::{Group, PrimeField, Ring, TwistedEdwardsAffine};
+use zkstd::common::{CurveGroup, Group, PrimeField, Ring, TwistedEdwardsAffine};
 
 /// instance for relaxed r1cs (E, u, x)
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Instance<C: TwistedEdwardsAffine> {
     /// commitment for witness vectors
     pub(crate) commit_w: C,
@@ -17,22 +20,147 @@ pub struct Instance<C: TwistedEdwardsAffine> {
 }
 
 impl<C: TwistedEdwardsAffine> Instance<C> {
+    /// commitment for witness vectors
+    pub fn commit_w(&self) -> C {
+        self.commit_w
+    }
+
+    /// commitment for error vectors
+    pub fn commit_e(&self) -> C {
+        self.commit_e
+    }
+
+    /// scalar
+    pub fn u(&self) -> C::Scalar {
+        self.u
+    }
+
+    /// public inputs and outputs
+    pub fn x(&self) -> &[C::Scalar] {
+        &self.x.0
+    }
+
+    /// trivial satisfying instance u⊥
     pub(crate) fn init(r1cs: R1csStructure<C>) -> Self {
         Self {
             commit_w: C::ADDITIVE_IDENTITY,
             commit_e: C::ADDITIVE_IDENTITY,
-            u: C::Scalar::one(),
+            u: C::Scalar::zero(),
             x: DenseVectors(vec![C::Scalar::zero(); r1cs.l]),
         }
     }
 
-    pub(crate) fn fold(&self, instance: R1csInstance<C::Scalar>, r: C::Scalar, t: C) -> Self {
+    /// absorb (commit_W, commit_E, u, x) into transcript
+    pub(crate) fn absorb<T: ChallengeTranscript<C>>(&self, transcript: &mut T) {
+        transcript.append_point(b"commit_w", &self.commit_w);
+        transcript.append_point(b"commit_e", &self.commit_e);
+        transcript.append_scalar(b"u", &self.u);
+        self.x
+            .iter()
+            .for_each(|x| transcript.append_scalar(b"x", &x));
+    }
+
+    /// absorb (commit_W, commit_E, u, x) into sponge
+    pub(crate) fn absorb_sponge(&self, sponge: &mut Poseidon<C::Scalar>) {
+        sponge.absorb_point(&self.commit_w);
+        sponge.absorb_point(&self.commit_e);
+        sponge.absorb(self.u);
+        self.x.iter().for_each(|x| sponge.absorb(x));
+    }
+
+    /// public io hash H(vk, i, z0, zi, U)
+    pub(crate) fn hash(
+        &self,
+        digest: C::Scalar,
+        i: usize,
+        z0: &[C::Scalar],
+        zi: &[C::Scalar],
+    ) -> C::Scalar {
+        let mut hasher = Poseidon::new(b"ivc");
+        hasher.absorb(digest);
+        hasher.absorb(C::Scalar::from(i as u64));
+        z0.iter().chain(zi.iter()).for_each(|z| hasher.absorb(*z));
+        self.absorb_sponge(&mut hasher);
+        hasher.squeeze()
+    }
+
+    /// public io hash H(vk, a, b, za, zb, U) of pcd node over range [a, b)
+    pub(crate) fn pcd_hash(
+        &self,
+        digest: C::Scalar,
+        range: (usize, usize),
+        z_in: &[C::Scalar],
+        z_out: &[C::Scalar],
+    ) -> C::Scalar {
+        let mut hasher = Poseidon::new(b"pcd");
+        hasher.absorb(digest);
+        hasher.absorb(C::Scalar::from(range.0 as u64));
+        hasher.absorb(C::Scalar::from(range.1 as u64));
+        z_in.iter()
+            .chain(z_out.iter())
+            .for_each(|z| hasher.absorb(*z));
+        self.absorb_sponge(&mut hasher);
+        hasher.squeeze()
+    }
+
+    pub(crate) fn fold(&self, instance: R1csInstance<C>, r: C::Scalar, t: C) -> Self {
         let r2 = r.square();
         let e1 = C::ADDITIVE_IDENTITY;
         let e2 = self.commit_e;
         let u1 = C::Scalar::one();
         let u2 = self.u;
-        let w1 = C::ADDITIVE_IDENTITY;
+        let w1 = instance.commit_w;
+        let w2 = self.commit_w;
+        let x1 = instance.x;
+        let x2 = self.x.clone();
+
+        Self {
+            commit_e: (e1 + t * r + e2 * r2).into(),
+            u: u1 + r * u2,
+            commit_w: (w1 + w2 * r).into(),
+            x: x1 + x2 * r,
+        }
+    }
+
+    /// fold k instances at once with U' = U + Σ r^i · ui and E' = E + Σ r^d · Td
+    pub(crate) fn fold_batch(
+        &self,
+        instances: Vec<R1csInstance<C>>,
+        r: C::Scalar,
+        t: &[C],
+    ) -> Self {
+        let powers = powers(r, t.len());
+        let (commit_w, u, x) = instances.into_iter().zip(powers.iter()).fold(
+            (C::Extended::ADDITIVE_IDENTITY, self.u, self.x.clone()),
+            |(commit_w, u, x), (instance, ri)| {
+                (
+                    commit_w + instance.commit_w * ri,
+                    u + ri,
+                    x + instance.x * *ri,
+                )
+            },
+        );
+        let commit_e = t
+            .iter()
+            .zip(powers.iter())
+            .fold(C::Extended::ADDITIVE_IDENTITY, |sum, (t, rd)| sum + *t * rd);
+
+        Self {
+            commit_e: (commit_e + self.commit_e).into(),
+            u,
+            commit_w: (commit_w + self.commit_w).into(),
+            x,
+        }
+    }
+
+    /// fold relaxed instance U1 into U2 with U = U1 + r · U2
+    pub(crate) fn fold_relaxed(&self, instance: Self, r: C::Scalar, t: C) -> Self {
+        let r2 = r.square();
+        let e1 = instance.commit_e;
+        let e2 = self.commit_e;
+        let u1 = instance.u;
+        let u2 = self.u;
+        let w1 = instance.commit_w;
         let w2 = self.commit_w;
         let x1 = instance.x;
         let x2 = self.x.clone();
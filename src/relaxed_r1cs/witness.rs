@@ -1,4 +1,5 @@
 use crate::matrix::DenseVectors;
+use crate::nifs::powers;
 use crate::r1cs::{R1csStructure, Witness as R1csWitness};
 
 use zkstd::common::{Group, PrimeField, TwistedEdwardsAffine};
@@ -9,17 +10,34 @@ pub struct Witness<C: TwistedEdwardsAffine> {
     pub(crate) w: DenseVectors<C::Scalar>,
     /// error vectors
     pub(crate) e: DenseVectors<C::Scalar>,
+    /// randomness for witness commitment
+    pub(crate) r_w: C::Scalar,
+    /// randomness for error commitment
+    pub(crate) r_e: C::Scalar,
 }
 
 impl<C: TwistedEdwardsAffine> Witness<C> {
+    /// witness
+    pub fn w(&self) -> &[C::Scalar] {
+        &self.w.0
+    }
+
+    /// error vectors
+    pub fn e(&self) -> &[C::Scalar] {
+        &self.e.0
+    }
+
     pub(crate) fn get(&self) -> (DenseVectors<C::Scalar>, DenseVectors<C::Scalar>) {
         (self.e.clone(), self.w.clone())
     }
 
-    pub(crate) fn init(r1cs: R1csStructure<C>) -> Self {
+    /// trivial satisfying witness w⊥ for n witness length
+    pub(crate) fn init(r1cs: R1csStructure<C>, n: usize) -> Self {
         Self {
-            w: DenseVectors(vec![C::Scalar::zero(); r1cs.m - r1cs.l]),
+            w: DenseVectors(vec![C::Scalar::zero(); n]),
             e: DenseVectors(vec![C::Scalar::zero(); r1cs.m]),
+            r_w: C::Scalar::zero(),
+            r_e: C::Scalar::zero(),
         }
     }
 
@@ -28,15 +46,68 @@ impl<C: TwistedEdwardsAffine> Witness<C> {
         witness: R1csWitness<C>,
         r: C::Scalar,
         t: DenseVectors<C::Scalar>,
+        r_t: C::Scalar,
     ) -> Self {
         let r2 = r.square();
         let e2 = self.e.clone();
         let w1 = witness.w;
         let w2 = self.w.clone();
+        let r_w1 = witness.r_w;
 
         Self {
             e: t * r + e2 * r2,
             w: w1 + w2 * r,
+            r_e: r_t * r + self.r_e * r2,
+            r_w: r_w1 + self.r_w * r,
+        }
+    }
+
+    /// fold relaxed witness W1 into W2 with E = E1 + r · T + r^2 · E2
+    pub(crate) fn fold_relaxed(
+        &self,
+        witness: Self,
+        r: C::Scalar,
+        t: DenseVectors<C::Scalar>,
+        r_t: C::Scalar,
+    ) -> Self {
+        let r2 = r.square();
+        let e1 = witness.e;
+        let e2 = self.e.clone();
+        let w1 = witness.w;
+        let w2 = self.w.clone();
+        let (r_e1, r_w1) = (witness.r_e, witness.r_w);
+
+        Self {
+            e: e1 + t * r + e2 * r2,
+            w: w1 + w2 * r,
+            r_e: r_e1 + r_t * r + self.r_e * r2,
+            r_w: r_w1 + self.r_w * r,
         }
     }
+
+    /// fold k witnesses at once with W' = W + Σ r^i · wi and E' = E + Σ r^d · Td
+    pub(crate) fn fold_batch(
+        &self,
+        witnesses: Vec<R1csWitness<C>>,
+        r: C::Scalar,
+        t: Vec<DenseVectors<C::Scalar>>,
+        r_t: Vec<C::Scalar>,
+    ) -> Self {
+        let powers = powers(r, t.len());
+        let (w, r_w) = witnesses
+            .into_iter()
+            .zip(powers.iter())
+            .fold((self.w.clone(), self.r_w), |(w, r_w), (witness, ri)| {
+                (w + witness.w * *ri, r_w + witness.r_w * ri)
+            });
+        let (e, r_e) = t
+            .into_iter()
+            .zip(r_t)
+            .zip(powers.iter())
+            .fold((self.e.clone(), self.r_e), |(e, r_e), ((t, r_t), rd)| {
+                (e + t * *rd, r_e + r_t * rd)
+            });
+
+        Self { e, w, r_e, r_w }
+    }
 }
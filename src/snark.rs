@@ -0,0 +1,297 @@
+mod ipa;
+mod polynomial;
+mod sumcheck;
+
+pub(crate) use ipa::InnerProductProof;
+pub(crate) use sumcheck::SumcheckProof;
+
+use crate::error::Error;
+use crate::matrix::SparseMatrix;
+use crate::nifs::VerificationKey;
+use crate::public_param::PedersenCommitment;
+use crate::r1cs::R1csStructure;
+use crate::relaxed_r1cs::{Instance as RelaxedR1csInstance, RelaxedR1csInstance as RelaxedR1cs};
+use crate::transcript::{ChallengeTranscript, Transcript};
+use crate::wire::Wire;
+pub(crate) use polynomial::{eq, eq_table, evaluate, pad};
+
+use zkstd::common::{Group, Ring, RngCore, TwistedEdwardsAffine};
+
+/// spartan style snark proving knowledge of (W, E) for committed relaxed r1cs
+/// outer sumcheck 0 = Σ_x eq(τ, x) · (Az(x) · Bz(x) - u · Cz(x) - E(x))
+/// inner sumcheck reduces Az, Bz and Cz at rx to Z(ry) = (1 - ry0) · W(ry') + ry0 · (u, x)(ry')
+/// where both halves of z are padded to n, then W(ry') and E(rx) are opened with ipa
+/// verifier evaluates sparse matrices at (rx, ry) in O(|A| + |B| + |C|)
+#[derive(Clone, Debug)]
+pub(crate) struct RelaxedR1csSnark<C: TwistedEdwardsAffine> {
+    pub(crate) outer: SumcheckProof<C::Scalar>,
+    /// Az(rx), Bz(rx) and Cz(rx)
+    pub(crate) claims: [C::Scalar; 3],
+    /// E(rx)
+    pub(crate) eval_e: C::Scalar,
+    pub(crate) inner: SumcheckProof<C::Scalar>,
+    /// W(ry')
+    pub(crate) eval_w: C::Scalar,
+    pub(crate) opening_w: InnerProductProof<C>,
+    pub(crate) opening_e: InnerProductProof<C>,
+}
+
+impl<C: TwistedEdwardsAffine> RelaxedR1csSnark<C> {
+    pub(crate) fn prove<T: ChallengeTranscript<C>>(
+        pp: &PedersenCommitment<C>,
+        relaxed_r1cs: &RelaxedR1cs<C>,
+        transcript: &mut T,
+        mut rng: impl RngCore,
+    ) -> Self {
+        let RelaxedR1cs {
+            r1cs,
+            instance,
+            witness,
+        } = relaxed_r1cs;
+        let (u, x, w) = (instance.u, &instance.x, &witness.w);
+        let rows = r1cs.m.next_power_of_two();
+        assert!(
+            w.0.len() <= witness_bound(r1cs),
+            "witness exceeds witness length of r1cs"
+        );
+        let n = witness_bound(r1cs).next_power_of_two();
+
+        // outer sumcheck over rows
+        let tau = challenges(transcript, b"tau", rows.trailing_zeros() as usize);
+        let e = pad(&witness.e.0, rows);
+        let tables = vec![
+            eq_table(&tau),
+            pad(&r1cs.a.prod(r1cs.m, u, x, w).0, rows),
+            pad(&r1cs.b.prod(r1cs.m, u, x, w).0, rows),
+            pad(&r1cs.c.prod(r1cs.m, u, x, w).0, rows),
+            e.clone(),
+        ];
+        let (outer, rx, evaluations) = SumcheckProof::prove(
+            tables,
+            3,
+            |v| v[0] * (v[1] * v[2] - u * v[3] - v[4]),
+            transcript,
+        );
+        let claims = [evaluations[1], evaluations[2], evaluations[3]];
+        let eval_e = evaluations[4];
+        claims
+            .iter()
+            .for_each(|claim| transcript.append_scalar(b"claim", claim));
+        transcript.append_scalar(b"eval_e", &eval_e);
+
+        // inner sumcheck over columns
+        let coeffs = challenges(transcript, b"inner", 3);
+        let w = pad(&w.0, n);
+        let z = w
+            .iter()
+            .copied()
+            .chain(pad(&[vec![u], x.0.clone()].concat(), n))
+            .collect();
+        let tables = vec![combined_table(r1cs, &coeffs, &eq_table(&rx), n), z];
+        let (inner, ry, _) = SumcheckProof::prove(tables, 2, |v| v[0] * v[1], transcript);
+        let eval_w = evaluate(&w, &ry[1..]);
+        transcript.append_scalar(b"eval_w", &eval_w);
+
+        // open W(ry') and E(rx)
+        let opening_w = InnerProductProof::prove(
+            pp,
+            &instance.commit_w,
+            &w,
+            witness.r_w,
+            &eq_table(&ry[1..]),
+            transcript,
+            &mut rng,
+        );
+        let opening_e = InnerProductProof::prove(
+            pp,
+            &instance.commit_e,
+            &e,
+            witness.r_e,
+            &eq_table(&rx),
+            transcript,
+            &mut rng,
+        );
+
+        Self {
+            outer,
+            claims,
+            eval_e,
+            inner,
+            eval_w,
+            opening_w,
+            opening_e,
+        }
+    }
+
+    pub(crate) fn verify<T: ChallengeTranscript<C>>(
+        &self,
+        pp: &PedersenCommitment<C>,
+        r1cs: &R1csStructure<C>,
+        instance: &RelaxedR1csInstance<C>,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        let u = instance.u;
+        let rows = r1cs.m.next_power_of_two();
+        // padded half length of z is derived from structure and fixes inner sumcheck rounds
+        let n = witness_bound(r1cs).next_power_of_two();
+        if self.inner.polys.len() != (2 * n).trailing_zeros() as usize {
+            return Err(Error::InvalidSumcheck);
+        }
+
+        // outer sumcheck
+        let tau = challenges(transcript, b"tau", rows.trailing_zeros() as usize);
+        let (claim, rx) = self.outer.verify(
+            C::Scalar::zero(),
+            rows.trailing_zeros() as usize,
+            3,
+            transcript,
+        )?;
+        let [va, vb, vc] = self.claims;
+        if claim != eq(&tau, &rx) * (va * vb - u * vc - self.eval_e) {
+            return Err(Error::InvalidSumcheck);
+        }
+        self.claims
+            .iter()
+            .for_each(|claim| transcript.append_scalar(b"claim", claim));
+        transcript.append_scalar(b"eval_e", &self.eval_e);
+
+        // inner sumcheck
+        let coeffs = challenges(transcript, b"inner", 3);
+        let claim = coeffs[0] * va + coeffs[1] * vb + coeffs[2] * vc;
+        let rounds = (2 * n).trailing_zeros() as usize;
+        let (claim, ry) = self.inner.verify(claim, rounds, 2, transcript)?;
+        let ux = pad(&[vec![u], instance.x.0.clone()].concat(), n);
+        let eval_z = (C::Scalar::one() - ry[0]) * self.eval_w + ry[0] * evaluate(&ux, &ry[1..]);
+        let eval_m = evaluate(&combined_table(r1cs, &coeffs, &eq_table(&rx), n), &ry);
+        if claim != eval_m * eval_z {
+            return Err(Error::InvalidSumcheck);
+        }
+        transcript.append_scalar(b"eval_w", &self.eval_w);
+
+        // openings
+        self.opening_w.verify(
+            pp,
+            &instance.commit_w,
+            &eq_table(&ry[1..]),
+            self.eval_w,
+            transcript,
+        )?;
+        self.opening_e.verify(
+            pp,
+            &instance.commit_e,
+            &eq_table(&rx),
+            self.eval_e,
+            transcript,
+        )
+    }
+}
+
+/// transcript for snark bound to verification key and instance
+pub(crate) fn transcript<C: TwistedEdwardsAffine>(
+    vk: &VerificationKey<C::Scalar>,
+    instance: &RelaxedR1csInstance<C>,
+) -> Transcript {
+    let mut transcript = <Transcript as ChallengeTranscript<C>>::init(b"relaxed_r1cs_snark");
+    <Transcript as ChallengeTranscript<C>>::append_scalar(&mut transcript, b"vk", &vk.digest);
+    instance.absorb(&mut transcript);
+    transcript
+}
+
+fn challenges<C: TwistedEdwardsAffine, T: ChallengeTranscript<C>>(
+    transcript: &mut T,
+    label: &'static [u8],
+    n: usize,
+) -> Vec<C::Scalar> {
+    (0..n).map(|_| transcript.challenge_scalar(label)).collect()
+}
+
+/// minimum half length of z covering witnesses and (u, x)
+fn witness_bound<C: TwistedEdwardsAffine>(r1cs: &R1csStructure<C>) -> usize {
+    r1cs.witness_len().max(r1cs.l + 1)
+}
+
+/// column of wire in z = (W, u, x) with halves of length n
+fn column(wire: Wire, n: usize) -> usize {
+    match wire {
+        Wire::Witness(i) => i,
+        Wire::One => n,
+        Wire::Instance(i) => n + 1 + i,
+    }
+}
+
+/// evaluations of ra · A(rx, y) + rb · B(rx, y) + rc · C(rx, y) over y
+fn combined_table<C: TwistedEdwardsAffine>(
+    r1cs: &R1csStructure<C>,
+    coeffs: &[C::Scalar],
+    eq_rx: &[C::Scalar],
+    n: usize,
+) -> Vec<C::Scalar> {
+    let mut table = vec![C::Scalar::zero(); 2 * n];
+    let matrices: [&SparseMatrix<C::Scalar>; 3] = [&r1cs.a, &r1cs.b, &r1cs.c];
+    matrices
+        .iter()
+        .zip(coeffs.iter())
+        .for_each(|(matrix, coeff)| {
+            matrix.0.iter().zip(eq_rx.iter()).for_each(|(row, e)| {
+                row.iter().for_each(|element| {
+                    let (wire, value) = element.get();
+                    table[column(wire, n)] += *coeff * *e * value;
+                })
+            })
+        });
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{transcript, RelaxedR1csSnark};
+    use crate::error::Error;
+    use crate::nifs::VerificationKey;
+    use crate::public_param::PedersenCommitment;
+    use crate::tests::{example_r1cs, example_relaxed_r1cs_instance};
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use rand_core::OsRng;
+
+    #[test]
+    fn relaxed_r1cs_snark_test() {
+        let r1cs = example_r1cs::<Curve>();
+        let pp = PedersenCommitment::from_r1cs(&r1cs, OsRng);
+        let vk = VerificationKey {
+            digest: pp.digest(),
+        };
+        let relaxed_r1cs = example_relaxed_r1cs_instance::<Curve>(&pp, 3);
+        let instance = &relaxed_r1cs.instance;
+
+        let snark =
+            RelaxedR1csSnark::prove(&pp, &relaxed_r1cs, &mut transcript(&vk, instance), OsRng);
+        assert!(snark
+            .verify(&pp, &r1cs, instance, &mut transcript(&vk, instance))
+            .is_ok());
+
+        let mut invalid_instance = instance.clone();
+        invalid_instance.u += Scalar::one();
+        assert!(snark
+            .verify(
+                &pp,
+                &r1cs,
+                &invalid_instance,
+                &mut transcript(&vk, &invalid_instance)
+            )
+            .is_err());
+
+        // inner sumcheck rounds must match padded length of z
+        let mut invalid_snark = snark.clone();
+        invalid_snark.inner.polys.push(vec![Scalar::one(); 3]);
+        assert_eq!(
+            invalid_snark.verify(&pp, &r1cs, instance, &mut transcript(&vk, instance)),
+            Err(Error::InvalidSumcheck)
+        );
+        let mut invalid_snark = snark;
+        invalid_snark.inner.polys.pop();
+        assert_eq!(
+            invalid_snark.verify(&pp, &r1cs, instance, &mut transcript(&vk, instance)),
+            Err(Error::InvalidSumcheck)
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::error::Error;
+use crate::public_param::{msm, PedersenCommitment};
+use crate::transcript::ChallengeTranscript;
+
+use zkstd::common::{Group, PrimeField, RngCore, TwistedEdwardsAffine};
+
+/// inner product argument for <a, b> = v with public b
+/// against pedersen commitment C = <a, G> + r · H where G are first |a| bases
+/// and next base U binds v
+#[derive(Clone, Debug)]
+pub(crate) struct InnerProductProof<C: TwistedEdwardsAffine> {
+    pub(crate) l: Vec<C>,
+    pub(crate) r: Vec<C>,
+    pub(crate) a: C::Scalar,
+    pub(crate) blind: C::Scalar,
+}
+
+impl<C: TwistedEdwardsAffine> InnerProductProof<C> {
+    pub(crate) fn prove<T: ChallengeTranscript<C>>(
+        pp: &PedersenCommitment<C>,
+        commitment: &C,
+        a: &[C::Scalar],
+        blind: C::Scalar,
+        b: &[C::Scalar],
+        transcript: &mut T,
+        mut rng: impl RngCore,
+    ) -> Self {
+        let n = a.len();
+        assert!(pp.g.len() > n, "public parameters are shorter than vectors");
+        let v = inner_product(a, b);
+        let u = bind_value(pp, commitment, n, v, transcript);
+
+        let (mut a, mut b, mut g, mut blind) = (a.to_vec(), b.to_vec(), pp.g[..n].to_vec(), blind);
+        let (mut ls, mut rs) = (vec![], vec![]);
+        while a.len() > 1 {
+            let half = a.len() / 2;
+            let (a_lo, a_hi) = a.split_at(half);
+            let (b_lo, b_hi) = b.split_at(half);
+            let (g_lo, g_hi) = g.split_at(half);
+            let (l_blind, r_blind) = (C::Scalar::random(&mut rng), C::Scalar::random(&mut rng));
+            let l: C = (msm(g_hi, a_lo) + u * inner_product(a_lo, b_hi) + pp.h * l_blind).into();
+            let r: C = (msm(g_lo, a_hi) + u * inner_product(a_hi, b_lo) + pp.h * r_blind).into();
+            transcript.append_point(b"ipa_l", &l);
+            transcript.append_point(b"ipa_r", &r);
+            let x = transcript.challenge_scalar(b"ipa_challenge");
+            let x_inv = x.invert().unwrap();
+
+            a = fold_scalars(a_lo, a_hi, x, x_inv);
+            b = fold_scalars(b_lo, b_hi, x_inv, x);
+            g = fold_points(g_lo, g_hi, x_inv, x);
+            blind = blind + l_blind * x.square() + r_blind * x_inv.square();
+            ls.push(l);
+            rs.push(r);
+        }
+
+        Self {
+            l: ls,
+            r: rs,
+            a: a[0],
+            blind,
+        }
+    }
+
+    pub(crate) fn verify<T: ChallengeTranscript<C>>(
+        &self,
+        pp: &PedersenCommitment<C>,
+        commitment: &C,
+        b: &[C::Scalar],
+        v: C::Scalar,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        let n = b.len();
+        let rounds = n.trailing_zeros() as usize;
+        if !n.is_power_of_two()
+            || pp.g.len() <= n
+            || self.l.len() != rounds
+            || self.r.len() != rounds
+        {
+            return Err(Error::InvalidOpening);
+        }
+        let u = bind_value(pp, commitment, n, v, transcript);
+
+        let mut c = *commitment + u * v;
+        let (mut b, mut g) = (b.to_vec(), pp.g[..n].to_vec());
+        for (l, r) in self.l.iter().zip(self.r.iter()) {
+            transcript.append_point(b"ipa_l", l);
+            transcript.append_point(b"ipa_r", r);
+            let x = transcript.challenge_scalar(b"ipa_challenge");
+            let x_inv = x.invert().ok_or(Error::InvalidOpening)?;
+
+            let half = b.len() / 2;
+            c = c + *l * x.square() + *r * x_inv.square();
+            b = fold_scalars(&b[..half], &b[half..], x_inv, x);
+            g = fold_points(&g[..half], &g[half..], x_inv, x);
+        }
+
+        let c: C = c.into();
+        let expected: C = (g[0] * self.a + u * (self.a * b[0]) + pp.h * self.blind).into();
+        if c == expected {
+            Ok(())
+        } else {
+            Err(Error::InvalidOpening)
+        }
+    }
+}
+
+/// U = ξ · G|a| with ξ ← p(C, v)
+fn bind_value<C: TwistedEdwardsAffine, T: ChallengeTranscript<C>>(
+    pp: &PedersenCommitment<C>,
+    commitment: &C,
+    n: usize,
+    v: C::Scalar,
+    transcript: &mut T,
+) -> C {
+    transcript.append_point(b"ipa_commitment", commitment);
+    transcript.append_scalar(b"ipa_value", &v);
+    let xi = transcript.challenge_scalar(b"ipa_base");
+    (pp.g[n] * xi).into()
+}
+
+pub(crate) fn inner_product<F: PrimeField>(a: &[F], b: &[F]) -> F {
+    a.iter()
+        .zip(b.iter())
+        .fold(F::zero(), |sum, (a, b)| sum + *a * *b)
+}
+
+fn fold_scalars<F: PrimeField>(lo: &[F], hi: &[F], x_lo: F, x_hi: F) -> Vec<F> {
+    lo.iter()
+        .zip(hi.iter())
+        .map(|(lo, hi)| *lo * x_lo + *hi * x_hi)
+        .collect()
+}
+
+fn fold_points<C: TwistedEdwardsAffine>(
+    lo: &[C],
+    hi: &[C],
+    x_lo: C::Scalar,
+    x_hi: C::Scalar,
+) -> Vec<C> {
+    lo.iter()
+        .zip(hi.iter())
+        .map(|(lo, hi)| (*lo * x_lo + *hi * x_hi).into())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{inner_product, InnerProductProof};
+    use crate::matrix::DenseVectors;
+    use crate::public_param::PedersenCommitment;
+    use crate::transcript::{ChallengeTranscript, Transcript};
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use rand_core::OsRng;
+    use zkstd::common::Group;
+
+    #[test]
+    fn inner_product_test() {
+        let pp = PedersenCommitment::<Curve>::new(9, OsRng);
+        let a = (0..8).map(|_| Scalar::random(OsRng)).collect::<Vec<_>>();
+        let b = (0..8).map(|_| Scalar::random(OsRng)).collect::<Vec<_>>();
+        let blind = Scalar::random(OsRng);
+        let commitment = pp.commit(&DenseVectors(a.clone()), &blind);
+        let v = inner_product(&a, &b);
+
+        let mut transcript = <Transcript as ChallengeTranscript<Curve>>::init(b"ipa");
+        let proof =
+            InnerProductProof::prove(&pp, &commitment, &a, blind, &b, &mut transcript, OsRng);
+
+        let mut transcript = <Transcript as ChallengeTranscript<Curve>>::init(b"ipa");
+        assert!(proof
+            .verify(&pp, &commitment, &b, v, &mut transcript)
+            .is_ok());
+
+        let mut transcript = <Transcript as ChallengeTranscript<Curve>>::init(b"ipa");
+        assert!(proof
+            .verify(&pp, &commitment, &b, v + Scalar::one(), &mut transcript)
+            .is_err());
+    }
+}
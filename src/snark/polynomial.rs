@@ -0,0 +1,88 @@
+use zkstd::common::PrimeField;
+
+/// evaluations of eq(r, x) over boolean hypercube with first variable as msb
+pub(crate) fn eq_table<F: PrimeField>(r: &[F]) -> Vec<F> {
+    r.iter().fold(vec![F::one()], |table, r| {
+        table
+            .iter()
+            .flat_map(|e| [*e * (F::one() - *r), *e * *r])
+            .collect()
+    })
+}
+
+/// eq(r, x) = Π (rk · xk + (1 - rk) · (1 - xk))
+pub(crate) fn eq<F: PrimeField>(r: &[F], x: &[F]) -> F {
+    r.iter().zip(x.iter()).fold(F::one(), |acc, (r, x)| {
+        acc * (*r * *x + (F::one() - *r) * (F::one() - *x))
+    })
+}
+
+/// multilinear extension of evaluations over boolean hypercube at r
+pub(crate) fn evaluate<F: PrimeField>(evaluations: &[F], r: &[F]) -> F {
+    evaluations
+        .iter()
+        .zip(eq_table(r))
+        .fold(F::zero(), |sum, (v, e)| sum + *v * e)
+}
+
+/// evaluate univariate polynomial given by evaluations at 0, 1, .., d at x
+pub(crate) fn interpolate<F: PrimeField>(evaluations: &[F], x: F) -> F {
+    let points = (0..evaluations.len())
+        .map(|i| F::from(i as u64))
+        .collect::<Vec<_>>();
+    evaluations
+        .iter()
+        .zip(points.iter())
+        .fold(F::zero(), |sum, (y, xi)| {
+            let (num, den) = points
+                .iter()
+                .filter(|xj| *xj != xi)
+                .fold((F::one(), F::one()), |(num, den), xj| {
+                    (num * (x - *xj), den * (*xi - *xj))
+                });
+            sum + *y * num * den.invert().unwrap()
+        })
+}
+
+/// pad vectors with zero to length n
+pub(crate) fn pad<F: PrimeField>(vectors: &[F], n: usize) -> Vec<F> {
+    let mut padded = vectors.to_vec();
+    padded.resize(n, F::zero());
+    padded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eq, eq_table, evaluate, interpolate};
+
+    use jub_jub::Fr as Scalar;
+    use rand_core::OsRng;
+    use zkstd::common::{Group, PrimeField};
+
+    #[test]
+    fn eq_table_test() {
+        let r = (0..3).map(|_| Scalar::random(OsRng)).collect::<Vec<_>>();
+        let table = eq_table(&r);
+        for (i, e) in table.iter().enumerate() {
+            let x = (0..3)
+                .map(|k| Scalar::from(((i >> (2 - k)) & 1) as u64))
+                .collect::<Vec<_>>();
+            assert_eq!(*e, eq(&r, &x))
+        }
+    }
+
+    #[test]
+    fn evaluate_test() {
+        // f(x0, x1) = 1 + 2 · x1 + 3 · x0 + 4 · x0 · x1
+        let evaluations = [1, 3, 4, 10].map(Scalar::from);
+        let (x0, x1) = (Scalar::from(5), Scalar::from(7));
+        let expected =
+            Scalar::one() + Scalar::from(2) * x1 + Scalar::from(3) * x0 + Scalar::from(4) * x0 * x1;
+        assert_eq!(evaluate(&evaluations, &[x0, x1]), expected);
+
+        // g(x) = x^2 + 1
+        let evaluations = [1, 2, 5].map(Scalar::from);
+        let x = Scalar::from(9);
+        assert_eq!(interpolate(&evaluations, x), x.square() + Scalar::one())
+    }
+}
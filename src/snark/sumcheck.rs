@@ -0,0 +1,93 @@
+use super::polynomial::interpolate;
+use crate::error::Error;
+use crate::transcript::ChallengeTranscript;
+
+use zkstd::common::{PrimeField, TwistedEdwardsAffine};
+
+/// sumcheck proof for Σ_x g(f1(x), .., fk(x)) over boolean hypercube
+/// each round polynomial is sent as evaluations at 0, 1, .., degree
+#[derive(Clone, Debug)]
+pub(crate) struct SumcheckProof<F: PrimeField> {
+    pub(crate) polys: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> SumcheckProof<F> {
+    /// prove sum of g over multilinear tables binding first variable each round
+    /// output proof, challenges and tables evaluated at challenges
+    pub(crate) fn prove<C, T>(
+        mut tables: Vec<Vec<F>>,
+        degree: usize,
+        g: impl Fn(&[F]) -> F,
+        transcript: &mut T,
+    ) -> (Self, Vec<F>, Vec<F>)
+    where
+        C: TwistedEdwardsAffine<Scalar = F>,
+        T: ChallengeTranscript<C>,
+    {
+        let rounds = tables[0].len().trailing_zeros() as usize;
+        let mut polys = Vec::with_capacity(rounds);
+        let mut challenges = Vec::with_capacity(rounds);
+
+        for _ in 0..rounds {
+            let half = tables[0].len() / 2;
+            let poly = (0..=degree)
+                .map(|x| {
+                    let x = F::from(x as u64);
+                    (0..half).fold(F::zero(), |sum, k| {
+                        let values = tables
+                            .iter()
+                            .map(|table| table[k] + x * (table[k + half] - table[k]))
+                            .collect::<Vec<_>>();
+                        sum + g(&values)
+                    })
+                })
+                .collect::<Vec<_>>();
+            poly.iter()
+                .for_each(|e| transcript.append_scalar(b"sumcheck_poly", e));
+            let r = transcript.challenge_scalar(b"sumcheck_challenge");
+
+            tables.iter_mut().for_each(|table| {
+                let bound = (0..half)
+                    .map(|k| table[k] + r * (table[k + half] - table[k]))
+                    .collect();
+                *table = bound;
+            });
+            polys.push(poly);
+            challenges.push(r);
+        }
+
+        let evaluations = tables.iter().map(|table| table[0]).collect();
+        (Self { polys }, challenges, evaluations)
+    }
+
+    /// reduce claimed sum to evaluation of g at challenges
+    /// output final claim and challenges
+    pub(crate) fn verify<C, T>(
+        &self,
+        claim: F,
+        rounds: usize,
+        degree: usize,
+        transcript: &mut T,
+    ) -> Result<(F, Vec<F>), Error>
+    where
+        C: TwistedEdwardsAffine<Scalar = F>,
+        T: ChallengeTranscript<C>,
+    {
+        if self.polys.len() != rounds {
+            return Err(Error::InvalidSumcheck);
+        }
+        let mut claim = claim;
+        let mut challenges = Vec::with_capacity(rounds);
+        for poly in self.polys.iter() {
+            if poly.len() != degree + 1 || poly[0] + poly[1] != claim {
+                return Err(Error::InvalidSumcheck);
+            }
+            poly.iter()
+                .for_each(|e| transcript.append_scalar(b"sumcheck_poly", e));
+            let r = transcript.challenge_scalar(b"sumcheck_challenge");
+            claim = interpolate(poly, r);
+            challenges.push(r);
+        }
+        Ok((claim, challenges))
+    }
+}
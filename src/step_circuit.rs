@@ -0,0 +1,16 @@
+use crate::constraint_system::ConstraintSystem;
+use crate::wire::Wire;
+
+use zkstd::common::TwistedEdwardsAffine;
+
+/// step function F for incrementally verifiable computation zi+1 = F(zi)
+pub trait StepCircuit<C: TwistedEdwardsAffine> {
+    /// length of z
+    fn arity(&self) -> usize;
+
+    /// constrain zi+1 = F(zi) and output zi+1 wires
+    fn synthesize(&self, cs: &mut ConstraintSystem<C>, z_in: &[Wire]) -> Vec<Wire>;
+
+    /// compute zi+1 = F(zi) natively
+    fn output(&self, z_in: &[C::Scalar]) -> Vec<C::Scalar>;
+}
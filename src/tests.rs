@@ -1,9 +1,14 @@
+use crate::constraint_system::ConstraintSystem;
+use crate::gadget::{alloc, mul};
 use crate::matrix::{Element, SparseMatrix};
+use crate::public_param::PedersenCommitment;
 use crate::r1cs::{R1csInstance, R1csStructure};
 use crate::relaxed_r1cs::RelaxedR1csInstance;
+use crate::step_circuit::StepCircuit;
 use crate::wire::Wire;
 
-use zkstd::common::{PrimeField, TwistedEdwardsAffine};
+use rand_core::OsRng;
+use zkstd::common::{Group, PrimeField, Ring, TwistedEdwardsAffine};
 
 pub(crate) fn array_to_witnessess<F: PrimeField>(witnesses: Vec<u64>) -> Vec<F> {
     witnesses
@@ -84,17 +89,82 @@ pub(crate) fn example_r1cs_witness<F: PrimeField>(input: u64) -> Vec<F> {
     ])
 }
 
-pub(crate) fn example_r1cs_instance<C: TwistedEdwardsAffine>(input: u64) -> R1csInstance<C> {
+pub(crate) fn example_r1cs_instance<C: TwistedEdwardsAffine>(
+    pp: &PedersenCommitment<C>,
+    input: u64,
+) -> R1csInstance<C> {
     let r1cs = example_r1cs();
     let z = example_r1cs_witness(input);
-    R1csInstance::new(&r1cs, &z)
+    R1csInstance::new(&r1cs, &z, pp, OsRng).unwrap()
 }
 
 pub(crate) fn example_relaxed_r1cs_instance<C: TwistedEdwardsAffine>(
+    pp: &PedersenCommitment<C>,
     input: u64,
 ) -> RelaxedR1csInstance<C> {
     let r1cs = example_r1cs();
     let z = example_r1cs_witness(input);
-    let r1cs_instance = R1csInstance::new(&r1cs, &z);
+    let r1cs_instance = R1csInstance::new(&r1cs, &z, pp, OsRng).unwrap();
     r1cs_instance.relax()
 }
+
+/// step circuit for: zi+1 = zi^3 + zi + 5
+pub(crate) struct ExampleCircuit;
+
+impl<C: TwistedEdwardsAffine> StepCircuit<C> for ExampleCircuit {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn synthesize(&self, cs: &mut ConstraintSystem<C>, z_in: &[Wire]) -> Vec<Wire> {
+        let x = z_in[0];
+        let xx = mul(cs, x, x);
+        let xxx = mul(cs, xx, x);
+        let five = C::Scalar::from(5);
+        vec![alloc(cs, vec![xxx.into(), x.into(), five.into()])]
+    }
+
+    fn output(&self, z_in: &[C::Scalar]) -> Vec<C::Scalar> {
+        let x = z_in[0];
+        vec![x.square() * x + x + C::Scalar::from(5)]
+    }
+}
+
+/// non-uniform step circuit for: (pc, x) → (1, x^3 + x + 5)
+pub(crate) struct CubeCircuit;
+
+impl<C: TwistedEdwardsAffine> StepCircuit<C> for CubeCircuit {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn synthesize(&self, cs: &mut ConstraintSystem<C>, z_in: &[Wire]) -> Vec<Wire> {
+        let pc = alloc(cs, vec![C::Scalar::one().into()]);
+        let x = ExampleCircuit.synthesize(cs, &z_in[1..]);
+        vec![pc, x[0]]
+    }
+
+    fn output(&self, z_in: &[C::Scalar]) -> Vec<C::Scalar> {
+        let x = StepCircuit::<C>::output(&ExampleCircuit, &z_in[1..]);
+        vec![C::Scalar::one(), x[0]]
+    }
+}
+
+/// non-uniform step circuit for: (pc, x) → (0, 2x)
+pub(crate) struct DoubleCircuit;
+
+impl<C: TwistedEdwardsAffine> StepCircuit<C> for DoubleCircuit {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn synthesize(&self, cs: &mut ConstraintSystem<C>, z_in: &[Wire]) -> Vec<Wire> {
+        let pc = alloc(cs, vec![]);
+        let x = alloc(cs, vec![Element(z_in[1], C::Scalar::from(2))]);
+        vec![pc, x]
+    }
+
+    fn output(&self, z_in: &[C::Scalar]) -> Vec<C::Scalar> {
+        vec![C::Scalar::zero(), z_in[1].double()]
+    }
+}
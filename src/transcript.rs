@@ -14,15 +14,13 @@ pub trait ChallengeTranscript<C: CurveAffine> {
 
 impl<C: CurveAffine> ChallengeTranscript<C> for Transcript {
     fn init(label: &[u8]) -> Self {
-        let label = unsafe { mem::transmute(label) };
+        let label = unsafe { mem::transmute::<&[u8], &'static [u8]>(label) };
         Transcript::new(label)
     }
 
     fn append_point(&mut self, label: &'static [u8], v: &C) {
-        let x = C::Scalar::from(v.get_x());
-        let y = C::Scalar::from(v.get_y());
-        <Transcript as ChallengeTranscript<C>>::append_scalar(self, label, &x);
-        <Transcript as ChallengeTranscript<C>>::append_scalar(self, label, &y);
+        self.append_message(label, &v.get_x().to_raw_bytes());
+        self.append_message(label, &v.get_y().to_raw_bytes());
     }
 
     fn append_scalar(&mut self, label: &'static [u8], v: &C::Scalar) {
@@ -0,0 +1,490 @@
+use crate::augmented_circuit::augmented_r1cs;
+use crate::error::Error;
+use crate::merge_circuit::merge_r1cs;
+use crate::nifs::{ivc_challenge, VerificationKey};
+use crate::nivc_circuit::{nivc_hash, nivc_r1cs};
+use crate::proof::{CompressedProof, IvcProof, NivcProof, PcdProof};
+use crate::public_param::PedersenCommitment;
+use crate::r1cs::{
+    Instance as R1csInstance, R1csInstance as R1cs, R1csStructure, Witness as R1csWitness,
+};
+use crate::relaxed_r1cs::{
+    Instance as RelaxedR1csInstance, RelaxedR1csInstance as RelaxedR1cs,
+    Witness as RelaxedR1csWitness,
+};
+use crate::snark;
+use crate::step_circuit::StepCircuit;
+
+use std::ops::Range;
+use zkstd::common::{Ring, TwistedEdwardsAffine};
+
+/// ivc verifier holding (pp, F', vk)
+pub struct Verifier<C: TwistedEdwardsAffine> {
+    pub(crate) pp: PedersenCommitment<C>,
+    pub(crate) f: R1csStructure<C>,
+    pub(crate) vk: VerificationKey<C::Scalar>,
+}
+
+impl<C: TwistedEdwardsAffine> Verifier<C> {
+    /// verifier for r1cs structure f
+    pub fn new(pp: PedersenCommitment<C>, f: R1csStructure<C>) -> Self {
+        let vk = VerificationKey::new(&pp, &[&f]);
+        Self { pp, f, vk }
+    }
+
+    /// verifier for F' derived from step circuit F
+    pub fn from_circuit(pp: PedersenCommitment<C>, circuit: &impl StepCircuit<C>) -> Self {
+        Self::new(pp, augmented_r1cs(circuit))
+    }
+
+    /// verification key
+    pub fn vk(&self) -> &VerificationKey<C::Scalar> {
+        &self.vk
+    }
+
+    /// V(vk, (i, z0, zi), Πi)
+    pub fn verify(
+        &self,
+        i: usize,
+        z0: &[C::Scalar],
+        zi: &[C::Scalar],
+        πi: &IvcProof<C>,
+    ) -> Result<(), Error> {
+        if i == 0 {
+            return if z0 == zi {
+                Ok(())
+            } else {
+                Err(Error::InvalidBaseCase)
+            };
+        }
+
+        // ui.x = H(vk, i, z0, zi, Ui)
+        let hash = πi.upper_pair.0.hash(self.vk.digest, i, z0, zi);
+        if πi.lower_pair.0.x.0.first() != Some(&hash) {
+            return Err(Error::InvalidIoHash);
+        }
+
+        self.decide(πi)
+    }
+
+    /// V(vk, (i, z0, zi), Πi) for compressed proof
+    /// folds (Ui, ui, T) into Ui+1 and verifies snark for Ui+1
+    pub fn verify_compressed(
+        &self,
+        i: usize,
+        z0: &[C::Scalar],
+        zi: &[C::Scalar],
+        πi: &CompressedProof<C>,
+    ) -> Result<(), Error> {
+        if i == 0 {
+            return if z0 == zi {
+                Ok(())
+            } else {
+                Err(Error::InvalidBaseCase)
+            };
+        }
+
+        let CompressedProof {
+            upper_instance,
+            lower_instance,
+            commit_t,
+            snark,
+        } = πi;
+
+        // ui.x = H(vk, i, z0, zi, Ui)
+        let hash = upper_instance.hash(self.vk.digest, i, z0, zi);
+        if lower_instance.x.0.first() != Some(&hash) {
+            return Err(Error::InvalidIoHash);
+        }
+
+        let r = ivc_challenge(&self.vk, upper_instance, lower_instance, commit_t);
+        let folded_instance = upper_instance.fold(lower_instance.clone(), r, *commit_t);
+        let mut transcript = snark::transcript(&self.vk, &folded_instance);
+        snark.verify(&self.pp, &self.f, &folded_instance, &mut transcript)
+    }
+
+    /// check that (Ui, Wi) and (ui, wi) of Πi are satisfying pairs
+    pub fn decide(&self, πi: &IvcProof<C>) -> Result<(), Error> {
+        let IvcProof {
+            upper_pair,
+            lower_pair,
+        } = πi.clone();
+        decide_strict(&self.pp, &self.f, lower_pair)?;
+        decide_relaxed(&self.pp, &self.f, upper_pair)
+    }
+}
+
+/// check that (u, w) is satisfying strict pair of f
+fn decide_strict<C: TwistedEdwardsAffine>(
+    pp: &PedersenCommitment<C>,
+    f: &R1csStructure<C>,
+    (instance, witness): (R1csInstance<C>, R1csWitness<C>),
+) -> Result<(), Error> {
+    if instance.x.0.len() != f.l
+        || witness.w.0.len() != f.witness_len()
+        || witness.w.0.len() > pp.size()
+    {
+        return Err(Error::InvalidLength);
+    }
+    // u is strict r1cs instance u = 1 and E = 0
+    if witness.one != C::Scalar::one() || witness.x != instance.x {
+        return Err(Error::InvalidStrictInstance);
+    }
+    if instance.commit_w != pp.commit(&witness.w, &witness.r_w) {
+        return Err(Error::InvalidCommitment);
+    }
+
+    let r1cs = R1cs {
+        r1cs: f.clone(),
+        instance,
+        witness,
+    };
+    if !r1cs.is_sat() {
+        return Err(Error::UnsatisfiedR1cs);
+    }
+    Ok(())
+}
+
+/// check that (U, W) is satisfying relaxed pair of f
+fn decide_relaxed<C: TwistedEdwardsAffine>(
+    pp: &PedersenCommitment<C>,
+    f: &R1csStructure<C>,
+    (instance, witness): (RelaxedR1csInstance<C>, RelaxedR1csWitness<C>),
+) -> Result<(), Error> {
+    if instance.x.0.len() != f.l
+        || witness.w.0.len() != f.witness_len()
+        || witness.e.0.len() != f.m
+        || f.witness_len().max(f.m) > pp.size()
+    {
+        return Err(Error::InvalidLength);
+    }
+    if instance.commit_w != pp.commit(&witness.w, &witness.r_w)
+        || instance.commit_e != pp.commit(&witness.e, &witness.r_e)
+    {
+        return Err(Error::InvalidCommitment);
+    }
+
+    let relaxed_r1cs = RelaxedR1cs {
+        r1cs: f.clone(),
+        instance,
+        witness,
+    };
+    if !relaxed_r1cs.is_sat() {
+        return Err(Error::UnsatisfiedRelaxedR1cs);
+    }
+    Ok(())
+}
+
+/// pcd verifier for merge circuit M derived from step circuit F
+pub struct PcdVerifier<C: TwistedEdwardsAffine> {
+    pub(crate) verifier: Verifier<C>,
+}
+
+impl<C: TwistedEdwardsAffine> PcdVerifier<C> {
+    /// verifier for M derived from step circuit F
+    pub fn from_circuit(pp: PedersenCommitment<C>, circuit: &impl StepCircuit<C>) -> Self {
+        Self {
+            verifier: Verifier::new(pp, merge_r1cs(circuit)),
+        }
+    }
+
+    /// verification key
+    pub fn vk(&self) -> &VerificationKey<C::Scalar> {
+        &self.verifier.vk
+    }
+
+    /// V(vk, (a, b, za, zb), Π) for node over range [a, b)
+    pub fn verify(
+        &self,
+        range: Range<usize>,
+        z_in: &[C::Scalar],
+        z_out: &[C::Scalar],
+        π: &PcdProof<C>,
+    ) -> Result<(), Error> {
+        if range.is_empty() {
+            return if z_in == z_out {
+                Ok(())
+            } else {
+                Err(Error::InvalidBaseCase)
+            };
+        }
+
+        // u.x = H(vk, a, b, za, zb, U)
+        let (start, end) = (range.start, range.end);
+        let hash =
+            π.proof
+                .upper_pair
+                .0
+                .pcd_hash(self.verifier.vk.digest, (start, end), z_in, z_out);
+        if π.proof.lower_pair.0.x.0.first() != Some(&hash) {
+            return Err(Error::InvalidIoHash);
+        }
+
+        self.verifier.decide(&π.proof)
+    }
+}
+
+/// non-uniform ivc verifier holding F'j for each step circuit Fj
+pub struct NivcVerifier<C: TwistedEdwardsAffine> {
+    pub(crate) pp: PedersenCommitment<C>,
+    pub(crate) f: Vec<R1csStructure<C>>,
+    pub(crate) vk: VerificationKey<C::Scalar>,
+}
+
+impl<C: TwistedEdwardsAffine> NivcVerifier<C> {
+    /// verifier for F'j derived from each step circuit Fj
+    pub fn from_circuits(pp: PedersenCommitment<C>, circuits: &[&dyn StepCircuit<C>]) -> Self {
+        let f = (0..circuits.len())
+            .map(|index| nivc_r1cs(circuits, index).0)
+            .collect::<Vec<_>>();
+        let vk = VerificationKey::new(&pp, &f.iter().collect::<Vec<_>>());
+        Self { pp, f, vk }
+    }
+
+    /// verification key
+    pub fn vk(&self) -> &VerificationKey<C::Scalar> {
+        &self.vk
+    }
+
+    /// V(vk, (i, z0, zi), Πi) checking ui against F'j which output it and U⃗i[k] against F'k
+    pub fn verify(
+        &self,
+        i: usize,
+        z0: &[C::Scalar],
+        zi: &[C::Scalar],
+        πi: &NivcProof<C>,
+    ) -> Result<(), Error> {
+        if i == 0 {
+            return if z0 == zi {
+                Ok(())
+            } else {
+                Err(Error::InvalidBaseCase)
+            };
+        }
+
+        let NivcProof {
+            upper_pairs,
+            lower_pair,
+            index,
+        } = πi.clone();
+        if upper_pairs.len() != self.f.len() || index >= self.f.len() {
+            return Err(Error::InvalidCircuitIndex);
+        }
+
+        // ui.x = H(vk, i, j, z0, zi, U⃗i)
+        let instances = upper_pairs
+            .iter()
+            .map(|(instance, _)| instance.clone())
+            .collect::<Vec<_>>();
+        let hash = nivc_hash(self.vk.digest, (i, index), z0, zi, &instances);
+        if lower_pair.0.x.0.first() != Some(&hash) {
+            return Err(Error::InvalidIoHash);
+        }
+
+        decide_strict(&self.pp, &self.f[index], lower_pair)?;
+        upper_pairs
+            .into_iter()
+            .zip(self.f.iter())
+            .try_for_each(|(upper_pair, f)| decide_relaxed(&self.pp, f, upper_pair))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NivcVerifier, PcdVerifier, Verifier};
+    use crate::error::Error;
+    use crate::merge_circuit::merge_r1cs;
+    use crate::nivc_circuit::nivc_r1cs;
+    use crate::prover::{NivcProver, PcdProver, Prover};
+    use crate::public_param::PedersenCommitment;
+    use crate::step_circuit::StepCircuit;
+    use crate::tests::{CubeCircuit, DoubleCircuit, ExampleCircuit};
+
+    use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+    use rand_core::OsRng;
+
+    #[test]
+    fn ivc_verify_test() {
+        let circuit = ExampleCircuit;
+        let pp = PedersenCommitment::<Curve>::from_circuit(&circuit, OsRng);
+        let verifier = Verifier::from_circuit(pp.clone(), &circuit);
+        let mut prover = Prover::from_circuit(pp, &circuit);
+        let z0 = vec![Scalar::from(3)];
+        let (mut zi, mut πi) = (z0.clone(), None);
+
+        for i in 1..=3 {
+            let (zi1, πi1) = prover.recurse(&circuit, &z0, &zi, πi, OsRng);
+
+            assert_eq!(verifier.verify(0, &z0, &z0, &πi1), Ok(()));
+            assert_eq!(
+                verifier.verify(0, &z0, &zi1, &πi1),
+                Err(Error::InvalidBaseCase)
+            );
+
+            assert_eq!(verifier.verify(i, &z0, &zi1, &πi1), Ok(()));
+            assert_eq!(
+                verifier.verify(i + 1, &z0, &zi1, &πi1),
+                Err(Error::InvalidIoHash)
+            );
+            assert_eq!(
+                verifier.verify(i, &z0, &zi, &πi1),
+                Err(Error::InvalidIoHash)
+            );
+
+            let mut invalid_πi = πi1.clone();
+            invalid_πi.upper_pair.1.e[0] += Scalar::one();
+            assert_eq!(
+                verifier.verify(i, &z0, &zi1, &invalid_πi),
+                Err(Error::InvalidCommitment)
+            );
+
+            let mut invalid_πi = πi1.clone();
+            invalid_πi.upper_pair.1.e.0.pop();
+            assert_eq!(
+                verifier.verify(i, &z0, &zi1, &invalid_πi),
+                Err(Error::InvalidLength)
+            );
+            let mut invalid_πi = πi1.clone();
+            invalid_πi.lower_pair.1.w.0.clear();
+            assert_eq!(
+                verifier.verify(i, &z0, &zi1, &invalid_πi),
+                Err(Error::InvalidLength)
+            );
+
+            // oversized witnesses are rejected before committing
+            let size = verifier.pp.size();
+            let mut invalid_πi = πi1.clone();
+            invalid_πi.lower_pair.1.w.0.resize(size + 1, Scalar::zero());
+            assert_eq!(
+                verifier.verify(i, &z0, &zi1, &invalid_πi),
+                Err(Error::InvalidLength)
+            );
+            let mut invalid_πi = πi1.clone();
+            invalid_πi.upper_pair.1.w.0.resize(size + 1, Scalar::zero());
+            assert_eq!(
+                verifier.verify(i, &z0, &zi1, &invalid_πi),
+                Err(Error::InvalidLength)
+            );
+
+            (zi, πi) = (zi1, Some(πi1))
+        }
+    }
+
+    #[test]
+    fn compressed_verify_test() {
+        let circuit = ExampleCircuit;
+        let pp = PedersenCommitment::<Curve>::from_circuit(&circuit, OsRng);
+        let verifier = Verifier::from_circuit(pp.clone(), &circuit);
+        let mut prover = Prover::from_circuit(pp, &circuit);
+        let z0 = vec![Scalar::from(3)];
+        let (mut zi, mut πi) = (z0.clone(), None);
+
+        for _ in 0..3 {
+            let (zi1, πi1) = prover.recurse(&circuit, &z0, &zi, πi, OsRng);
+            (zi, πi) = (zi1, Some(πi1))
+        }
+        let compressed = prover.compress(πi.unwrap(), OsRng);
+
+        assert_eq!(verifier.verify_compressed(3, &z0, &zi, &compressed), Ok(()));
+        assert_eq!(
+            verifier.verify_compressed(2, &z0, &zi, &compressed),
+            Err(Error::InvalidIoHash)
+        );
+
+        let mut invalid_compressed = compressed;
+        invalid_compressed.snark.eval_w += Scalar::one();
+        assert!(verifier
+            .verify_compressed(3, &z0, &zi, &invalid_compressed)
+            .is_err());
+    }
+
+    #[test]
+    fn pcd_verify_test() {
+        let circuit = ExampleCircuit;
+        let pp = PedersenCommitment::<Curve>::from_r1cs(&merge_r1cs(&circuit), OsRng);
+        let verifier = PcdVerifier::from_circuit(pp.clone(), &circuit);
+        let prover = PcdProver::from_circuit(pp, &circuit);
+        let z0 = vec![Scalar::from(3)];
+        let zs = (0..5).fold(vec![z0.clone()], |mut zs, i| {
+            zs.push(StepCircuit::<Curve>::output(&circuit, &zs[i]));
+            zs
+        });
+
+        // leaves [0, 2), [2, 4) and [4, 5) merged on two threads
+        let π = prover.prove(&circuit, &z0, 5, 2, 2, || OsRng);
+
+        assert_eq!(π.range(), 0..5);
+        assert_eq!(π.z_out(), &zs[5][..]);
+        assert_eq!(verifier.verify(0..5, &z0, &zs[5], &π), Ok(()));
+        assert_eq!(
+            verifier.verify(0..4, &z0, &zs[5], &π),
+            Err(Error::InvalidIoHash)
+        );
+        assert_eq!(
+            verifier.verify(0..5, &z0, &zs[4], &π),
+            Err(Error::InvalidIoHash)
+        );
+
+        let mut invalid_π = π;
+        invalid_π.proof.upper_pair.1.e[0] += Scalar::one();
+        assert_eq!(
+            verifier.verify(0..5, &z0, &zs[5], &invalid_π),
+            Err(Error::InvalidCommitment)
+        );
+    }
+
+    #[test]
+    fn nivc_verify_test() {
+        let circuits: [&dyn StepCircuit<Curve>; 2] = [&CubeCircuit, &DoubleCircuit];
+        let size = (0..2)
+            .map(|index| PedersenCommitment::r1cs_size(&nivc_r1cs(&circuits, index).0))
+            .max()
+            .unwrap();
+        let pp = PedersenCommitment::<Curve>::new(size, OsRng);
+        let verifier = NivcVerifier::from_circuits(pp.clone(), &circuits);
+        let mut prover = NivcProver::from_circuits(pp, &circuits);
+        let z0 = vec![Scalar::zero(), Scalar::from(3)];
+        let (mut zi, mut πi) = (z0.clone(), None);
+
+        // F0 and F1 alternate by program counter
+        for i in 1..=4 {
+            let (zi1, πi1) = prover.recurse(&circuits, &z0, &zi, πi, OsRng);
+
+            assert_eq!(πi1.index(), (i - 1) % 2);
+            assert_eq!(zi1, circuits[(i - 1) % 2].output(&zi));
+            assert_eq!(verifier.verify(i, &z0, &zi1, &πi1), Ok(()));
+            assert_eq!(
+                verifier.verify(i + 1, &z0, &zi1, &πi1),
+                Err(Error::InvalidIoHash)
+            );
+
+            // F1 running instance is untouched until its first instance is folded
+            if i < 3 {
+                assert_eq!(πi1.upper_instance(1).u, Scalar::zero());
+            }
+
+            let mut invalid_πi = πi1.clone();
+            invalid_πi.index = 1 - πi1.index;
+            assert_eq!(
+                verifier.verify(i, &z0, &zi1, &invalid_πi),
+                Err(Error::InvalidIoHash)
+            );
+
+            let mut invalid_πi = πi1.clone();
+            invalid_πi.index = 2;
+            assert_eq!(
+                verifier.verify(i, &z0, &zi1, &invalid_πi),
+                Err(Error::InvalidCircuitIndex)
+            );
+
+            let mut invalid_πi = πi1.clone();
+            invalid_πi.upper_pairs[0].1.e[0] += Scalar::one();
+            assert_eq!(
+                verifier.verify(i, &z0, &zi1, &invalid_πi),
+                Err(Error::InvalidCommitment)
+            );
+
+            (zi, πi) = (zi1, Some(πi1))
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use nova::{ConstraintSystem, Nifs, PedersenCommitment, R1cs, R1csStructure};
+
+use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+use rand_core::OsRng;
+use zkstd::common::{CurveGroup, PrimeField};
+
+/// constraint system for: x^3 + x + 5 = y
+fn example_cs(x: u64) -> ConstraintSystem<Curve> {
+    let x = Scalar::from(x);
+    let (xx, xxx) = (x.square(), x.square() * x);
+    let five = Scalar::from(5);
+
+    let mut cs = ConstraintSystem::new();
+    let (a, b, c) = (
+        cs.public_wire(x),
+        cs.public_wire(xxx + x + five),
+        cs.public_wire(five),
+    );
+    let (d, e, f) = (
+        cs.private_wire(xx),
+        cs.private_wire(xxx),
+        cs.private_wire(xxx + x),
+    );
+    cs.mul_constraint(a, a, d);
+    cs.mul_constraint(d, a, e);
+    cs.add_constraint(e, a, f);
+    cs.add_constraint(f, c, b);
+    cs
+}
+
+fn example_r1cs(pp: &PedersenCommitment<Curve>, x: u64) -> R1cs<Curve> {
+    let cs = example_cs(x);
+    R1cs::new(&cs.structure(), &cs.assignment(), pp, OsRng).unwrap()
+}
+
+fn setup() -> (R1csStructure<Curve>, PedersenCommitment<Curve>) {
+    let structure = example_cs(0).structure();
+    let pp = PedersenCommitment::from_r1cs(&structure, OsRng);
+    (structure, pp)
+}
+
+#[test]
+fn r1cs_test() {
+    let (structure, pp) = setup();
+    assert_eq!(structure.m(), 4);
+    assert_eq!(structure.l(), 3);
+
+    let r1cs = example_r1cs(&pp, 3);
+    assert!(r1cs.is_sat());
+    assert_eq!(r1cs.instance().x(), r1cs.witness().x());
+    assert_eq!(r1cs.instance().x()[1], Scalar::from(35));
+
+    let relaxed_r1cs = r1cs.relax();
+    assert!(relaxed_r1cs.is_sat());
+    assert_eq!(relaxed_r1cs.instance().u(), Scalar::one());
+    assert_eq!(
+        relaxed_r1cs.instance().commit_w(),
+        r1cs.instance().commit_w()
+    );
+    assert_eq!(relaxed_r1cs.instance().commit_e(), Curve::ADDITIVE_IDENTITY);
+    assert!(relaxed_r1cs
+        .witness()
+        .e()
+        .iter()
+        .all(|e| *e == Scalar::zero()));
+}
+
+#[test]
+fn nifs_test() {
+    let (structure, pp) = setup();
+    let (prover, vk) = Nifs::k(pp.clone(), structure);
+
+    // vk binds parameters and shape which doesn't depend on assignment
+    let mut other_cs = ConstraintSystem::<Curve>::new();
+    let x = other_cs.public_wire(Scalar::one());
+    other_cs.mul_constraint(x, x, x);
+    assert_ne!(vk.digest(), pp.digest());
+    assert_eq!(
+        Nifs::k(pp.clone(), example_cs(7).structure()).1.digest(),
+        vk.digest()
+    );
+    assert_ne!(
+        Nifs::k(pp.clone(), other_cs.structure()).1.digest(),
+        vk.digest()
+    );
+
+    let mut relaxed_r1cs = example_r1cs(&pp, 1).relax();
+    for i in 2..10 {
+        let r1cs = example_r1cs(&pp, i);
+        let (instance, relaxed_instance) =
+            (r1cs.instance().clone(), relaxed_r1cs.instance().clone());
+        let (folded_r1cs, commit_t) = prover.prove(r1cs, relaxed_r1cs, OsRng);
+        let folded_instance = Nifs::verify(&vk, relaxed_instance, instance, commit_t);
+
+        assert!(folded_r1cs.is_sat());
+        assert_eq!(folded_r1cs.instance(), &folded_instance);
+        relaxed_r1cs = folded_r1cs
+    }
+}
+
+#[test]
+fn finalize_test() {
+    let (structure, pp) = setup();
+    let (finalized, instance, witness) = example_cs(3).finalize(&pp, OsRng);
+    assert_eq!(finalized.l(), structure.l());
+    assert_eq!(finalized.digest(), structure.digest());
+    assert_eq!(instance.x(), witness.x());
+
+    // finalized circuits connect to folding
+    let (prover, vk) = Nifs::k(pp.clone(), finalized);
+    let mut relaxed_r1cs = example_cs(1).into_r1cs(&pp, OsRng).relax();
+    for i in 2..5 {
+        let r1cs = example_cs(i).into_r1cs(&pp, OsRng);
+        assert!(r1cs.is_sat());
+        let (instance, relaxed_instance) =
+            (r1cs.instance().clone(), relaxed_r1cs.instance().clone());
+        let (folded_r1cs, commit_t) = prover.prove(r1cs, relaxed_r1cs, OsRng);
+        assert!(folded_r1cs.is_sat());
+        assert_eq!(
+            Nifs::verify(&vk, relaxed_instance, instance, commit_t),
+            folded_r1cs.instance().clone()
+        );
+        relaxed_r1cs = folded_r1cs
+    }
+}
@@ -0,0 +1,82 @@
+use nova::{ConstraintSystem, Error, PedersenCommitment, Prover, StepCircuit, Verifier, Wire};
+
+use jub_jub::{Fr as Scalar, JubjubAffine as Curve};
+use rand_core::OsRng;
+use zkstd::common::PrimeField;
+
+/// step circuit for: zi+1 = zi^2 + zi
+struct SquareCircuit;
+
+impl StepCircuit<Curve> for SquareCircuit {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn synthesize(&self, cs: &mut ConstraintSystem<Curve>, z_in: &[Wire]) -> Vec<Wire> {
+        let x = z_in[0];
+        let xx = cs.private_wire(cs.value(x).square());
+        cs.mul_constraint(x, x, xx);
+        let y = cs.private_wire(cs.value(xx) + cs.value(x));
+        cs.add_constraint(xx, x, y);
+        vec![y]
+    }
+
+    fn output(&self, z_in: &[Scalar]) -> Vec<Scalar> {
+        vec![z_in[0].square() + z_in[0]]
+    }
+}
+
+fn setup() -> (Prover<Curve>, Verifier<Curve>) {
+    let circuit = SquareCircuit;
+    let pp = PedersenCommitment::from_circuit(&circuit, OsRng);
+    let verifier = Verifier::from_circuit(pp.clone(), &circuit);
+    let prover = Prover::from_circuit(pp, &circuit);
+    assert_eq!(prover.vk().digest(), verifier.vk().digest());
+    (prover, verifier)
+}
+
+#[test]
+fn ivc_test() {
+    let circuit = SquareCircuit;
+    let (mut prover, verifier) = setup();
+    let z0 = vec![Scalar::from(2)];
+    let (mut zi, mut proof) = (z0.clone(), None);
+
+    for i in 1..=3 {
+        let (zi1, proof1) = prover.recurse(&circuit, &z0, &zi, proof, OsRng);
+
+        assert_eq!(prover.steps(), i);
+        assert_eq!(zi1, circuit.output(&zi));
+        assert_eq!(proof1.lower_instance().x(), proof1.lower_witness().x());
+        assert_eq!(verifier.verify(i, &z0, &zi1, &proof1), Ok(()));
+        assert_eq!(
+            verifier.verify(i, &z0, &zi, &proof1),
+            Err(Error::InvalidIoHash)
+        );
+
+        (zi, proof) = (zi1, Some(proof1))
+    }
+}
+
+#[test]
+fn compressed_ivc_test() {
+    let circuit = SquareCircuit;
+    let (mut prover, verifier) = setup();
+    let z0 = vec![Scalar::from(2)];
+    let (mut zi, mut proof) = (z0.clone(), None);
+
+    for _ in 0..3 {
+        let (zi1, proof1) = prover.recurse(&circuit, &z0, &zi, proof, OsRng);
+        (zi, proof) = (zi1, Some(proof1))
+    }
+    let proof = proof.unwrap();
+    assert_eq!(verifier.decide(&proof), Ok(()));
+
+    let compressed = prover.compress(proof.clone(), OsRng);
+    assert_eq!(compressed.upper_instance(), proof.upper_instance());
+    assert_eq!(verifier.verify_compressed(3, &z0, &zi, &compressed), Ok(()));
+    assert_eq!(
+        verifier.verify_compressed(3, &z0, &z0, &compressed),
+        Err(Error::InvalidIoHash)
+    );
+}